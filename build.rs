@@ -0,0 +1,31 @@
+use std::{env, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/days");
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let days_dir = Path::new(&manifest_dir).join("src/days");
+
+    let mut days: Vec<u32> = fs::read_dir(&days_dir)
+        .unwrap()
+        .filter_map(|e| {
+            let name = e.ok()?.file_name().into_string().ok()?;
+            name.strip_prefix("day")?.strip_suffix(".rs")?.parse().ok()
+        })
+        .collect();
+    days.sort();
+
+    let mut registry = String::new();
+    registry.push_str("pub fn registry() -> HashMap<i32, Box<dyn Day>> {\n");
+    registry.push_str("    let mut programs: HashMap<i32, Box<dyn Day>> = HashMap::new();\n");
+    for day in &days {
+        registry.push_str(&format!(
+            "    programs.insert({0}, Box::new(day{0}::Instance));\n",
+            day
+        ));
+    }
+    registry.push_str("    programs\n}\n");
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("registry.rs");
+    fs::write(out, registry).unwrap();
+}
@@ -0,0 +1,344 @@
+use std::{
+    fmt::Display,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Unchecked,
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+}
+
+impl FromStr for Verdict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "unchecked" => Verdict::Unchecked,
+            "correct" => Verdict::Correct,
+            "too-high" => Verdict::TooHigh,
+            "too-low" => Verdict::TooLow,
+            "wrong" => Verdict::Wrong,
+            v => Err(format!("Unknown verdict: {}", v))?,
+        })
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Verdict::Unchecked => "unchecked",
+            Verdict::Correct => "correct",
+            Verdict::TooHigh => "too-high",
+            Verdict::TooLow => "too-low",
+            Verdict::Wrong => "wrong",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    timestamp: u64,
+    part: u8,
+    answer: String,
+    input_hash: u64,
+    verdict: Verdict,
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split('\t').collect();
+        let bad = || format!("Bad answer entry: {:?}", s);
+        let [timestamp, part, input_hash, verdict, answer] = fields[..] else {
+            return Err(bad());
+        };
+
+        Ok(Entry {
+            timestamp: timestamp.parse().map_err(|_| bad())?,
+            part: part.parse().map_err(|_| bad())?,
+            input_hash: u64::from_str_radix(input_hash, 16).map_err(|_| bad())?,
+            verdict: verdict.parse()?,
+            answer: answer.to_owned(),
+        })
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{:016x}\t{}\t{}",
+            self.timestamp, self.part, self.input_hash, self.verdict, self.answer
+        )
+    }
+}
+
+pub enum Check {
+    New,
+    Known(Verdict),
+    Impossible(String),
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Check::New | Check::Known(Verdict::Unchecked) => Ok(()),
+            Check::Known(Verdict::Correct) => write!(f, " (correct)"),
+            Check::Known(v) => write!(f, " (WARNING: already marked {})", v),
+            Check::Impossible(reason) => write!(f, " (WARNING: cannot be right, {})", reason),
+        }
+    }
+}
+
+pub struct AnswerStore {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl AnswerStore {
+    pub fn load(day: i32) -> Result<Self, String> {
+        AnswerStore::load_from(PathBuf::from(format!("answers/day{}.txt", day)))
+    }
+
+    // Only a missing file starts an empty store, anything else would be overwritten on save
+    fn load_from(path: PathBuf) -> Result<Self, String> {
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .map(|l| l.parse())
+                .collect::<Result<_, _>>()?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+
+        Ok(AnswerStore { path, entries })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let contents: String = self.entries.iter().map(|e| format!("{}\n", e)).collect();
+        fs::write(&self.path, contents).map_err(|e| e.to_string())
+    }
+
+    pub fn check(&self, part: u8, answer: &str, input_hash: u64) -> Check {
+        let same_input = || {
+            self.entries
+                .iter()
+                .filter(move |e| e.part == part && e.input_hash == input_hash)
+        };
+
+        if let Some(e) =
+            same_input().find(|e| e.answer == answer && e.verdict != Verdict::Unchecked)
+        {
+            return Check::Known(e.verdict);
+        }
+
+        if let Some(e) = same_input().find(|e| e.verdict == Verdict::Correct) {
+            return Check::Impossible(format!("{} is correct", e.answer));
+        }
+
+        if let Ok(value) = answer.parse::<i128>() {
+            let bound = |verdict| {
+                same_input()
+                    .filter(move |e| e.verdict == verdict)
+                    .filter_map(|e| e.answer.parse::<i128>().ok())
+            };
+            if let Some(high) = bound(Verdict::TooHigh).min().filter(|&h| value >= h) {
+                return Check::Impossible(format!("{} was too high", high));
+            }
+            if let Some(low) = bound(Verdict::TooLow).max().filter(|&l| value <= l) {
+                return Check::Impossible(format!("{} was too low", low));
+            }
+        }
+
+        Check::New
+    }
+
+    // Entries are tab separated lines, so answers can't span fields or lines
+    fn validate(answer: &str) -> Result<(), String> {
+        if answer.contains(['\t', '\n', '\r']) {
+            return Err(format!("Cannot record answer {:?}", answer));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, part: u8, answer: &str, input_hash: u64, verdict: Verdict) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        self.entries.push(Entry {
+            timestamp,
+            part,
+            answer: answer.to_owned(),
+            input_hash,
+            verdict,
+        });
+    }
+
+    // A repeated answer moves to the end rather than being added again, so it stays the latest
+    pub fn record(&mut self, part: u8, answer: &str, input_hash: u64) -> Result<(), String> {
+        AnswerStore::validate(answer)?;
+        let same = |e: &Entry| e.part == part && e.input_hash == input_hash && e.answer == answer;
+        let verdict = self
+            .entries
+            .iter()
+            .find(|e| same(e))
+            .map_or(Verdict::Unchecked, |e| e.verdict);
+
+        self.entries.retain(|e| !same(e));
+        self.push(part, answer, input_hash, verdict);
+        self.save()
+    }
+
+    pub fn mark(
+        &mut self,
+        part: u8,
+        answer: Option<&str>,
+        input_hash: u64,
+        verdict: Verdict,
+    ) -> Result<String, String> {
+        let answer = match answer {
+            Some(a) => {
+                AnswerStore::validate(a)?;
+                a.to_owned()
+            }
+            None => self
+                .entries
+                .iter()
+                .rev()
+                .find(|e| e.part == part && e.input_hash == input_hash)
+                .ok_or(format!("No recorded answer for part {}", part))?
+                .answer
+                .clone(),
+        };
+
+        let mut marked = false;
+        for e in self
+            .entries
+            .iter_mut()
+            .filter(|e| e.part == part && e.input_hash == input_hash && e.answer == answer)
+        {
+            e.verdict = verdict;
+            marked = true;
+        }
+        if !marked {
+            self.push(part, &answer, input_hash, verdict);
+        }
+
+        self.save()?;
+        Ok(answer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(entries: &[(u8, &str, Verdict)]) -> AnswerStore {
+        let mut store = AnswerStore {
+            path: PathBuf::new(),
+            entries: Vec::new(),
+        };
+        for &(part, answer, verdict) in entries {
+            store.push(part, answer, 1, verdict);
+        }
+        store
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let line = "1700000000\t2\t00000000000000ff\ttoo-low\t12345";
+        let entry: Entry = line.parse().unwrap();
+        assert_eq!(entry.verdict, Verdict::TooLow);
+        assert_eq!(entry.input_hash, 255);
+        assert_eq!(entry.to_string(), line);
+    }
+
+    #[test]
+    fn rejects_answers_outside_bounds() {
+        let store = store(&[
+            (1, "100", Verdict::TooHigh),
+            (1, "50", Verdict::TooLow),
+            (1, "70", Verdict::Wrong),
+            (2, "10", Verdict::TooHigh),
+        ]);
+
+        assert!(matches!(
+            store.check(1, "70", 1),
+            Check::Known(Verdict::Wrong)
+        ));
+        assert!(matches!(store.check(1, "120", 1), Check::Impossible(_)));
+        assert!(matches!(
+            store.check(1, "50", 1),
+            Check::Known(Verdict::TooLow)
+        ));
+        assert!(matches!(store.check(1, "42", 1), Check::Impossible(_)));
+        assert!(matches!(store.check(1, "60", 1), Check::New));
+        assert!(matches!(store.check(1, "120", 2), Check::New));
+        assert!(matches!(store.check(1, "abc", 1), Check::New));
+    }
+
+    #[test]
+    fn warns_against_correct_answers() {
+        let store = store(&[(1, "7", Verdict::Wrong), (1, "42", Verdict::Correct)]);
+
+        assert!(matches!(
+            store.check(1, "42", 1),
+            Check::Known(Verdict::Correct)
+        ));
+        assert!(matches!(store.check(1, "43", 1), Check::Impossible(_)));
+        assert!(matches!(store.check(2, "43", 1), Check::New));
+    }
+
+    #[test]
+    fn loads_only_missing_files_as_empty() {
+        let dir = std::env::temp_dir().join(format!("aoc23-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let missing = AnswerStore::load_from(dir.join("day1.txt")).unwrap();
+        assert!(missing.entries.is_empty());
+        // A directory cannot be read as a file
+        assert!(AnswerStore::load_from(dir.clone()).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn records_each_answer_once() {
+        let dir = std::env::temp_dir().join(format!("aoc23-answers-{}", std::process::id()));
+        let mut store = store(&[(1, "7", Verdict::Wrong)]);
+        store.path = dir.join("day1.txt");
+
+        store.record(1, "8", 1).unwrap();
+        store.record(1, "7", 1).unwrap();
+        store.record(1, "7", 1).unwrap();
+        assert!(store.record(1, "7\t8", 1).is_err());
+
+        let answers: Vec<_> = store
+            .entries
+            .iter()
+            .map(|e| (e.answer.as_str(), e.verdict))
+            .collect();
+        assert_eq!(
+            answers,
+            vec![("8", Verdict::Unchecked), ("7", Verdict::Wrong)]
+        );
+
+        let loaded = fs::read_to_string(&store.path).unwrap();
+        assert_eq!(loaded.lines().count(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
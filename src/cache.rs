@@ -0,0 +1,91 @@
+use std::{fs, path::PathBuf};
+
+use crate::days::day::{DayResult, RunOptions};
+
+pub struct ResultCache {
+    path: PathBuf,
+    entries: Vec<(String, DayResult)>,
+}
+
+impl ResultCache {
+    pub fn load(day: i32) -> Self {
+        let path = PathBuf::from(format!("cache/day{}.txt", day));
+        // A missing or unreadable cache just means recomputing
+        let entries = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| {
+                let mut fields = l.split('\t');
+                let key = fields.next()?.to_owned();
+                let part1 = fields.next()?.to_owned();
+                let part2 = fields.next().map(|s| s.to_owned());
+                Some((key, DayResult { part1, part2 }))
+            })
+            .collect();
+
+        ResultCache { path, entries }
+    }
+
+    pub fn key(input_hash: u64, version: u32, options: &RunOptions) -> String {
+        let mode = if options.reference { "-reference" } else { "" };
+        let params: String = options
+            .params
+            .iter()
+            .map(|(k, v)| format!("-{}={}", k, v))
+            .collect();
+        format!("{:016x}-v{}{}{}", input_hash, version, mode, params)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DayResult> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, r)| r)
+    }
+
+    pub fn insert(&mut self, key: String, result: &DayResult) -> Result<(), String> {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push((key, result.clone()));
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|(k, r)| match &r.part2 {
+                Some(p2) => format!("{}\t{}\t{}\n", k, r.part1, p2),
+                None => format!("{}\t{}\n", k, r.part1),
+            })
+            .collect();
+        fs::write(&self.path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_bump_misses() {
+        let dir = std::env::temp_dir().join(format!("aoc23-cache-{}", std::process::id()));
+        let mut cache = ResultCache {
+            path: dir.join("day1.txt"),
+            entries: Vec::new(),
+        };
+        let options = RunOptions::default();
+        let result = DayResult {
+            part1: "1".to_owned(),
+            part2: None,
+        };
+
+        cache
+            .insert(ResultCache::key(7, 1, &options), &result)
+            .unwrap();
+        assert_eq!(
+            cache
+                .get(&ResultCache::key(7, 1, &options))
+                .map(|r| &r.part1),
+            Some(&result.part1)
+        );
+        assert!(cache.get(&ResultCache::key(7, 2, &options)).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,8 +1,57 @@
+use std::path::PathBuf;
+
+use super::input::Input;
+
+#[derive(Debug, Clone)]
 pub struct DayResult {
     pub part1: String,
     pub part2: Option<String>,
 }
 
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    pub reference: bool,
+    pub visualize: bool,
+    pub export: Option<PathBuf>,
+    pub graph: Option<PathBuf>,
+    pub verbose: bool,
+    pub params: Vec<(String, String)>,
+}
+
+impl RunOptions {
+    // Later values win so a repeated --param overrides an earlier one
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
 pub trait Day {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String>;
+
+    fn run_input(&self, input: &Input, _options: &RunOptions) -> Result<DayResult, String> {
+        self.run(input.to_lines())
+    }
+
+    // Bump whenever a change could alter the answers, so cached results are dropped
+    fn version(&self) -> u32 {
+        1
+    }
+
+    // Answers to extra questions asked with --param, as (question, answer) pairs
+    fn queries(
+        &self,
+        _input: &Input,
+        _options: &RunOptions,
+    ) -> Result<Vec<(String, String)>, String> {
+        Ok(Vec::new())
+    }
+
+    // Files besides the input that the answers depend on, so their contents join the cache key
+    fn extra_inputs(&self, _options: &RunOptions) -> Vec<PathBuf> {
+        Vec::new()
+    }
 }
@@ -1,48 +1,192 @@
-use super::day::*;
+use std::{fs, path::PathBuf};
+
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::matcher::Matcher,
+};
 
 pub struct Instance;
 
-fn calibration_value(lines: &[String], scores: &Vec<(String, u32)>) -> u32 {
-    lines
+const LANGUAGES: [(&str, [&str; 9]); 4] = [
+    (
+        "en",
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ],
+    ),
+    (
+        "fr",
+        [
+            "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+        ],
+    ),
+    (
+        "de",
+        [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ],
+    ),
+    (
+        "es",
+        [
+            "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+        ],
+    ),
+];
+
+fn digits() -> Vec<(String, u32)> {
+    (1..=9).map(|i| (i.to_string(), i)).collect()
+}
+
+fn words(language: &str) -> Result<Vec<(String, u32)>, String> {
+    let (_, words) = LANGUAGES
+        .iter()
+        .find(|(l, _)| *l == language)
+        .ok_or(format!("Unknown language: {}", language))?;
+
+    Ok(words
         .iter()
+        .zip(1..)
+        .map(|(w, i)| (w.to_string(), i))
+        .collect())
+}
+
+// One token per line as `word value`
+fn parse_vocabulary(contents: &str) -> Result<Vec<(String, u32)>, String> {
+    contents
+        .lines()
+        .filter(|l| !l.trim().is_empty())
         .map(|l| {
-            let mut first = None;
-            let mut last = None;
-            for i in 0..l.len() {
-                for (m, s) in scores {
-                    if l[i..].starts_with(m) {
-                        if first.is_none() {
-                            first = Some(*s)
-                        }
-                        last = Some(*s)
-                    }
-                }
+            let (word, value) = l
+                .split_once(char::is_whitespace)
+                .ok_or(format!("Bad vocabulary entry: {:?}", l))?;
+            if word.is_empty() {
+                return Err(format!("Empty vocabulary word: {:?}", l));
             }
-            first.unwrap_or(0) * 10 + last.unwrap_or(0)
+            let value = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Bad vocabulary value: {:?}", l))?;
+            Ok((word.to_owned(), value))
         })
-        .sum()
+        .collect()
+}
+
+fn read_vocabulary(path: &str) -> Result<Vec<(String, u32)>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    parse_vocabulary(&contents)
+}
+
+fn calibration_value(
+    lines: &[String],
+    matcher: &Matcher<u32>,
+    verbose: bool,
+) -> Result<u32, String> {
+    lines.iter().try_fold(0u32, |total, l| {
+        let matches = matcher.find_all(l);
+        // Both ends prefer the earlier vocabulary entry among tokens starting together
+        let first = matches.first().map_or(0, |m| *m.value);
+        let last = matches.last().map_or(0, |last| {
+            *matches[matches.partition_point(|m| m.start < last.start)].value
+        });
+        let value = first
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(last))
+            .ok_or(format!("Overflow calibrating {}", l))?;
+        if verbose {
+            let tokens: Vec<_> = matches.iter().map(|m| m.pattern).collect();
+            println!("{}: {:?} -> {}", l, tokens, value);
+        }
+        total
+            .checked_add(value)
+            .ok_or("Overflow summing calibration values".to_owned())
+    })
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let mut scores: Vec<_> = (1..=9).map(|i| (i.to_string(), i)).collect();
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let part1: u32 = calibration_value(&lines, &scores);
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
 
-        let words: Vec<_> = [
-            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-        ]
-        .iter()
-        .enumerate()
-        .map(|(i, &w)| (w.to_owned(), (i + 1) as u32))
-        .collect();
+        let part1 = calibration_value(&lines, &Matcher::new(digits()), options.verbose)?;
 
-        scores.extend(words);
+        let words = match options.param("vocabulary") {
+            Some(path) => read_vocabulary(path)?,
+            None => words(options.param("language").unwrap_or("en"))?,
+        };
+        let mut vocabulary = digits();
+        vocabulary.extend(words);
 
-        let part2 = calibration_value(&lines, &scores);
+        let part2 = calibration_value(&lines, &Matcher::new(vocabulary), options.verbose)?;
         Ok(DayResult {
             part1: part1.to_string(),
             part2: Some(part2.to_string()),
         })
     }
+
+    fn version(&self) -> u32 {
+        3
+    }
+
+    fn extra_inputs(&self, options: &RunOptions) -> Vec<PathBuf> {
+        options
+            .param("vocabulary")
+            .map(PathBuf::from)
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earlier_vocabulary_wins_ties() {
+        let lines = vec!["ab".to_owned()];
+        let vocabulary = |words: &[(&str, u32)]| {
+            Matcher::new(words.iter().map(|&(w, v)| (w.to_owned(), v)).collect())
+        };
+
+        assert_eq!(
+            calibration_value(&lines, &vocabulary(&[("ab", 1), ("a", 2)]), false),
+            Ok(11)
+        );
+        assert_eq!(
+            calibration_value(&lines, &vocabulary(&[("a", 2), ("ab", 1)]), false),
+            Ok(22)
+        );
+
+        // The last token starts at "b", so its tie is broken separately
+        assert_eq!(
+            calibration_value(&lines, &vocabulary(&[("a", 1), ("b", 2), ("b", 3)]), false),
+            Ok(12)
+        );
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let lines = vec!["ab".to_owned()];
+        let matcher = Matcher::new(vec![("a".to_owned(), u32::MAX / 10), ("b".to_owned(), 9)]);
+        assert!(calibration_value(&lines, &matcher, false).is_err());
+
+        let matcher = Matcher::new(vec![("a".to_owned(), u32::MAX / 20)]);
+        assert!(calibration_value(&lines, &matcher, false).is_ok());
+        let lines = vec!["ab".to_owned(), "ab".to_owned()];
+        assert!(calibration_value(&lines, &matcher, false).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_words() {
+        assert_eq!(
+            parse_vocabulary("uno 1\n\ndos 2\n").unwrap(),
+            vec![("uno".to_owned(), 1), ("dos".to_owned(), 2)]
+        );
+        assert!(parse_vocabulary(" 1").is_err());
+        assert!(parse_vocabulary("uno").is_err());
+    }
 }
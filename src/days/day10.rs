@@ -1,9 +1,18 @@
 use std::{
     collections::{HashMap, HashSet},
     iter,
+    str::FromStr,
 };
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        export::{Drawing, Rgb},
+        polygon::Polygon,
+        render::{Animation, Canvas, Color},
+    },
+};
 
 pub struct Instance;
 
@@ -224,7 +233,7 @@ impl Map {
         }
     }
 
-    fn count_inside(&self) -> usize {
+    fn count_inside(&self, mut animation: Option<&mut Animation>) -> usize {
         let path = self.find_loop();
         let mut inside_outside = InOutMap::new(&mut self.0.keys(), &self.find_loop());
 
@@ -287,7 +296,10 @@ impl Map {
                             | (InsideOutside::Outside, InsideOutside::Outside) => (),
                             (InsideOutside::Either, i) => {
                                 *v = *i;
-                                inside_outside.flood()
+                                inside_outside.flood();
+                                if let Some(animation) = animation.as_deref_mut() {
+                                    animation.frame(|| inside_outside.canvas());
+                                }
                             }
                         }
                     }
@@ -311,17 +323,20 @@ impl Map {
                 }
             }
         }
-        inside_outside.print();
         inside_outside.inside()
     }
 
     fn count_inside_rays(&self) -> usize {
+        self.inside_rays().len()
+    }
+
+    fn inside_rays(&self) -> Vec<Coord> {
         let path: HashSet<Coord> = HashSet::from_iter(self.find_loop());
 
         let max_x = self.0.keys().map(|c| c.0).max().unwrap();
         let max_y = self.0.keys().map(|c| c.1).max().unwrap();
 
-        let mut total_inside = 0;
+        let mut inside = Vec::new();
         for x in 1..=max_x {
             for y in 1..=max_y {
                 // Don't include the loop
@@ -334,7 +349,7 @@ impl Map {
                     .zip((1..y).rev()) // project ray to top left corner
                     .filter(|(x, y)| {
                         let coord = &Coord(*x, *y);
-                        path.contains(coord) 
+                        path.contains(coord)
                             // skirts corners so doesn't cross
                             && !matches!(self.0[coord], Pipe::BendNe | Pipe::BendSw)
                     })
@@ -342,12 +357,78 @@ impl Map {
 
                 // Odd crossings means it's left the inside
                 if crossings % 2 == 1 {
-                    total_inside += 1
+                    inside.push(Coord(x, y))
                 }
             }
         }
 
-        total_inside
+        inside
+    }
+
+    fn count_inside_polygon(&self) -> Result<usize, String> {
+        let polygon = Polygon::new(
+            self.find_loop()
+                .iter()
+                .map(|c| (c.0 as i64, c.1 as i64))
+                .collect(),
+        );
+        Ok(polygon.interior_points()? as usize)
+    }
+
+    fn drawing(&self) -> Drawing {
+        let path = self.find_loop();
+        let inside: HashSet<Coord> = HashSet::from_iter(self.inside_rays());
+
+        let max_x = self.0.keys().map(|c| c.0).max().unwrap();
+        let max_y = self.0.keys().map(|c| c.1).max().unwrap();
+
+        let mut drawing = Drawing::new(
+            (1.0, 1.0),
+            (max_x as f64 + 1.0, max_y as f64 + 1.0),
+            Rgb::GRAY,
+        );
+        for c in &inside {
+            drawing.rect(c.0 as f64, c.1 as f64, 1.0, 1.0, Rgb::GREEN);
+        }
+        drawing.polyline(
+            path.iter()
+                .map(|c| (c.0 as f64 + 0.5, c.1 as f64 + 0.5))
+                .collect(),
+            Rgb::DARK,
+            0.4,
+        );
+        drawing
+    }
+
+    fn canvas(&self) -> Canvas {
+        let path: HashSet<Coord> = HashSet::from_iter(self.find_loop());
+        let inside: HashSet<Coord> = HashSet::from_iter(self.inside_rays());
+
+        let max_x = self.0.keys().map(|c| c.0).max().unwrap();
+        let max_y = self.0.keys().map(|c| c.1).max().unwrap();
+
+        let mut canvas = Canvas::new(max_x as usize, max_y as usize);
+        for (c, pipe) in &self.0 {
+            let (ch, color) = if path.contains(c) {
+                let ch = match pipe {
+                    Pipe::Vertical => '│',
+                    Pipe::Horizontal => '─',
+                    Pipe::BendNe => '└',
+                    Pipe::BendNw => '┘',
+                    Pipe::BendSw => '┐',
+                    Pipe::BendSe => '┌',
+                    Pipe::Ground => '.',
+                    Pipe::Start => 'S',
+                };
+                (ch, Color::Yellow)
+            } else if inside.contains(c) {
+                ('I', Color::Green)
+            } else {
+                ('O', Color::Gray)
+            };
+            canvas.set(c.0 as usize - 1, c.1 as usize - 1, ch, Some(color));
+        }
+        canvas
     }
 }
 
@@ -428,37 +509,71 @@ impl<'a> InOutMap<'a> {
             .count()
     }
 
-    fn print(&self) {
+    fn canvas(&self) -> Canvas {
         let max_x = self.0.keys().map(|c| c.0).max().unwrap();
         let max_y = self.0.keys().map(|c| c.1).max().unwrap();
 
+        let mut canvas = Canvas::new(max_x as usize, max_y as usize);
         for y in 1..=max_y {
             for x in 1..=max_x {
-                let c = match self.0.get(&Coord(x, y)) {
-                    Some(InsideOutside::Inside) => 'I',
-                    Some(InsideOutside::Outside) => 'O',
-                    Some(InsideOutside::Either) => 'E',
-                    _ => '.',
+                let (c, color) = match self.0.get(&Coord(x, y)) {
+                    Some(InsideOutside::Inside) => ('I', Some(Color::Green)),
+                    Some(InsideOutside::Outside) => ('O', Some(Color::Blue)),
+                    Some(InsideOutside::Either) => ('E', Some(Color::Magenta)),
+                    _ => ('.', None),
                 };
-                print!("{}", c)
+                canvas.set(x as usize - 1, y as usize - 1, c, color);
             }
-            println!()
         }
+        canvas
     }
 }
 
-const USE_ORIGINAL_SOLUTION: bool = false;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InsideSolver {
+    // The only solver that animates under --visualize
+    Flood,
+    Rays,
+    Polygon,
+}
+
+impl FromStr for InsideSolver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flood" => Ok(InsideSolver::Flood),
+            "rays" => Ok(InsideSolver::Rays),
+            "polygon" => Ok(InsideSolver::Polygon),
+            _ => Err(format!("Unknown inside solver: {}", s)),
+        }
+    }
+}
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let map = Map::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let solver: InsideSolver = options.param("inside").unwrap_or("polygon").parse()?;
+        let map = Map::from_lines(&input.to_lines());
+        let mut animation = options.visualize.then(|| Animation::new(1));
 
         let part1 = map.find_distance().to_string();
-        let part2 = if USE_ORIGINAL_SOLUTION {
-            map.count_inside().to_string()
-        } else {
-            map.count_inside_rays().to_string()
-        };
+        let part2 = match solver {
+            InsideSolver::Flood => map.count_inside(animation.as_mut()),
+            InsideSolver::Rays => map.count_inside_rays(),
+            InsideSolver::Polygon => map.count_inside_polygon()?,
+        }
+        .to_string();
+
+        if let Some(animation) = &animation {
+            animation.show(&map.canvas());
+        }
+        if let Some(path) = &options.export {
+            map.drawing().save(path)?;
+        }
 
         Ok(DayResult {
             part1,
@@ -466,3 +581,31 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inside_solvers_agree() {
+        let lines: Vec<String> = [
+            "...........",
+            ".S-------7.",
+            ".|F-----7|.",
+            ".||.....||.",
+            ".||.....||.",
+            ".|L-7.F-J|.",
+            ".|..|.|..|.",
+            ".L--J.L--J.",
+            "...........",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let map = Map::from_lines(&lines);
+
+        assert_eq!(map.count_inside(None), 4);
+        assert_eq!(map.count_inside_rays(), 4);
+        assert_eq!(map.count_inside_polygon().unwrap(), 4);
+    }
+}
@@ -1,8 +1,12 @@
-use std::{collections::HashMap, str::FromStr};
+use std::str::FromStr;
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::memo::{Memo, MemoStats},
+};
 
 pub struct Instance;
 
@@ -47,69 +51,54 @@ impl FromStr for Record {
 }
 
 impl Record {
-    fn combinations(&self) -> usize {
-        let mut cache: HashMap<(Vec<State>, Vec<usize>), usize> = HashMap::new();
-        fn cached_result(
-            condition: &[State],
-            contiguity: &[usize],
-            cache: &mut HashMap<(Vec<State>, Vec<usize>), usize>,
-        ) -> usize {
-            let key = (condition.to_vec(), contiguity.to_vec());
-            if let Some(v) = cache.get(&key) {
-                return *v;
-            }
+    fn combinations(&self) -> (usize, MemoStats) {
+        let count = |memo: &mut Memo<(usize, usize), usize>, (c, g)| {
+            let condition = &self.condition[c..];
+            let contiguity = &self.contiguity[g..];
 
-            let ret = if contiguity.is_empty() {
-                if condition.iter().any(|c| matches!(c, State::Damaged)) {
+            if contiguity.is_empty() {
+                return if condition.iter().any(|c| matches!(c, State::Damaged)) {
                     0
                 } else {
                     1
+                };
+            }
+
+            let broken: usize = contiguity.iter().sum();
+            let gaps = contiguity.len() - 1;
+            let Some(spaces) = condition.len().checked_sub(gaps + broken) else {
+                return 0;
+            };
+            let current = contiguity[0];
+            let mut total = 0;
+
+            for i in 0..=spaces {
+                let end = i + current;
+                if end > condition.len() {
+                    break;
                 }
-            } else {
-                let broken: usize = contiguity.iter().sum();
-                let gaps = contiguity.len() - 1;
-                let state = condition.len();
-                let spaces = state - gaps - broken;
-                let current = contiguity[0];
-                let mut total = 0;
-
-                for i in 0..=spaces {
-                    let mut candidate = Vec::new();
-                    candidate.extend(vec![State::Operational; i]);
-                    candidate.extend(vec![State::Damaged; current]);
-
-                    match candidate.len().cmp(&condition.len()) {
-                        std::cmp::Ordering::Less => candidate.push(State::Operational),
-                        std::cmp::Ordering::Equal => (),
-                        std::cmp::Ordering::Greater => break,
-                    }
-
-                    let is_invalid = candidate.iter().zip(condition).any(|s| {
-                        matches!(
-                            s,
-                            (State::Damaged, State::Operational)
-                                | (State::Operational, State::Damaged)
-                        )
-                    });
-
-                    if is_invalid {
-                        continue;
-                    }
-
-                    total += cached_result(&condition[candidate.len()..], &contiguity[1..], cache);
+
+                let is_invalid = condition[..i].contains(&State::Damaged)
+                    || condition[i..end].contains(&State::Operational)
+                    || condition.get(end) == Some(&State::Damaged);
+
+                if is_invalid {
+                    continue;
                 }
 
-                total
-            };
+                let next = (end + 1).min(condition.len());
+                total += memo.get((c + next, g + 1));
+            }
 
-            cache.insert(key, ret);
-            ret
-        }
+            total
+        };
 
-        cached_result(&self.condition, &self.contiguity, &mut cache)
+        let mut memo = Memo::new(&count);
+        let total = memo.get((0, 0));
+        (total, memo.stats())
     }
 
-    fn times_five_combinations(&self) -> usize {
+    fn times_five_combinations(&self) -> (usize, MemoStats) {
         self.times(5).combinations()
     }
 
@@ -127,25 +116,66 @@ impl Record {
     }
 }
 
+// Sums the arrangements of every record, along with how well the memo did
+fn total(
+    records: &[Record],
+    combinations: impl Fn(&Record) -> (usize, MemoStats),
+) -> (usize, MemoStats) {
+    records
+        .iter()
+        .map(combinations)
+        .fold((0, MemoStats::default()), |(total, stats), (n, s)| {
+            (total + n, stats + s)
+        })
+}
+
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let records: Vec<_> = lines.iter().map(|l| l.parse::<Record>()).try_collect()?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let records: Vec<_> = input.lines().map(|l| l.parse::<Record>()).try_collect()?;
 
-        let part1 = records
-            .iter()
-            .map(|r| r.combinations())
-            .sum::<usize>()
-            .to_string();
+        let (part1, stats1) = total(&records, Record::combinations);
+        let (part2, stats2) = total(&records, Record::times_five_combinations);
 
-        let part2 = records
-            .iter()
-            .map(|r| r.times_five_combinations())
-            .sum::<usize>()
-            .to_string();
+        if options.verbose {
+            println!("Part 1 memo: {}", stats1);
+            println!("Part 2 memo: {}", stats2);
+        }
 
         Ok(DayResult {
-            part1,
-            part2: Some(part2),
+            part1: part1.to_string(),
+            part2: Some(part2.to_string()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_example_arrangements() {
+        let input = Input::read("fixtures/day12.txt").unwrap();
+        let records: Vec<Record> = input.lines().map(|l| l.parse()).try_collect().unwrap();
+
+        let counts: Vec<_> = records.iter().map(|r| r.combinations().0).collect();
+        assert_eq!(counts, vec![1, 4, 1, 1, 4, 10]);
+
+        // The last record revisits states, the first never does
+        let (_, stats) = records[0].combinations();
+        assert_eq!((stats.hits, stats.misses), (0, 4));
+        let (_, stats) = records[5].combinations();
+        assert_eq!((stats.hits, stats.misses), (7, 9));
+
+        let (arrangements, stats) = total(&records, Record::combinations);
+        assert_eq!(arrangements, 21);
+        assert_eq!((stats.hits, stats.misses), (13, 37));
+
+        let (arrangements, stats) = total(&records, Record::times_five_combinations);
+        assert_eq!(arrangements, 525152);
+        assert_eq!((stats.hits, stats.misses), (117, 205));
+    }
+}
@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult},
+    util::parse::sections,
+};
 
 pub struct Instance;
 
@@ -29,7 +32,7 @@ struct Map {
 }
 
 impl Map {
-    fn from_lines(lines: &[&String]) -> Self {
+    fn from_lines(lines: &[String]) -> Self {
         let mut max_x = 0;
         let mut max_y = 0;
         let mut map = HashMap::new();
@@ -186,12 +189,9 @@ impl Map {
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let maps = lines
-            .iter()
-            .group_by(|l| l.as_str() != "")
+        let maps = sections(&lines)
             .into_iter()
-            .filter(|s| s.0)
-            .map(|s| Map::from_lines(&s.1.collect_vec()))
+            .map(Map::from_lines)
             .collect_vec();
 
         let part1: usize = maps.iter().map(|m| m.reflect()).sum();
@@ -203,3 +203,18 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::input::Input;
+
+    #[test]
+    fn example() {
+        let input = Input::read("fixtures/day13.txt").unwrap();
+        let result = Instance.run_input(&input, &Default::default()).unwrap();
+
+        assert_eq!(result.part1, "405");
+        assert_eq!(result.part2.as_deref(), Some("400"));
+    }
+}
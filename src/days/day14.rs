@@ -2,7 +2,11 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::render::{Animation, Canvas, Color},
+};
 
 pub struct Instance;
 
@@ -13,10 +17,10 @@ enum Rock {
 }
 
 impl Rock {
-    fn from_char(c: char) -> Option<Self> {
+    fn from_byte(c: u8) -> Option<Self> {
         match c {
-            'O' => Some(Self::Round),
-            '#' => Some(Self::Cube),
+            b'O' => Some(Self::Round),
+            b'#' => Some(Self::Cube),
             _ => None,
         }
     }
@@ -30,15 +34,15 @@ struct Platform {
 }
 
 impl Platform {
-    fn from_lines(lines: &[String]) -> Self {
+    fn from_input(input: &Input) -> Self {
         let mut max_x = 0;
         let mut max_y = 0;
         let mut rocks = HashMap::new();
-        for (y, l) in lines.iter().enumerate() {
+        for (y, l) in input.byte_lines().enumerate() {
             max_y = max_y.max(y);
-            for (x, c) in l.chars().enumerate() {
+            for (x, &c) in l.iter().enumerate() {
                 max_x = max_x.max(x);
-                if let Some(rock) = Rock::from_char(c) {
+                if let Some(rock) = Rock::from_byte(c) {
                     rocks.insert((x, y), rock);
                 }
             }
@@ -59,7 +63,7 @@ impl Platform {
                     loop {
                         if y > 0 {
                             y -= 1;
-                            if self.rocks.get(&(x, y)).is_some() {
+                            if self.rocks.contains_key(&(x, y)) {
                                 y += 1;
                                 break;
                             }
@@ -82,7 +86,7 @@ impl Platform {
                     loop {
                         if y < (self.y_len - 1) {
                             y += 1;
-                            if self.rocks.get(&(x, y)).is_some() {
+                            if self.rocks.contains_key(&(x, y)) {
                                 y -= 1;
                                 break;
                             }
@@ -105,7 +109,7 @@ impl Platform {
                     loop {
                         if x < (self.x_len - 1) {
                             x += 1;
-                            if self.rocks.get(&(x, y)).is_some() {
+                            if self.rocks.contains_key(&(x, y)) {
                                 x -= 1;
                                 break;
                             }
@@ -128,7 +132,7 @@ impl Platform {
                     loop {
                         if x > 0 {
                             x -= 1;
-                            if self.rocks.get(&(x, y)).is_some() {
+                            if self.rocks.contains_key(&(x, y)) {
                                 x += 1;
                                 break;
                             }
@@ -142,11 +146,34 @@ impl Platform {
         }
     }
 
-    fn cycle(&mut self) {
-        self.tilt_north();
-        self.tilt_west();
-        self.tilt_south();
-        self.tilt_east();
+    fn cycle(&mut self, mut animation: Option<&mut Animation>) {
+        let tilts = [
+            Self::tilt_north,
+            Self::tilt_west,
+            Self::tilt_south,
+            Self::tilt_east,
+        ];
+        for tilt in tilts {
+            tilt(self);
+            if let Some(animation) = animation.as_deref_mut() {
+                animation.frame(|| self.canvas());
+            }
+        }
+    }
+
+    fn canvas(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.x_len, self.y_len);
+        for y in 0..self.y_len {
+            for x in 0..self.x_len {
+                let (c, color) = match self.rocks.get(&(x, y)) {
+                    Some(Rock::Round) => ('O', Color::Cyan),
+                    Some(Rock::Cube) => ('#', Color::Yellow),
+                    None => ('.', Color::Gray),
+                };
+                canvas.set(x, y, c, Some(color));
+            }
+        }
+        canvas
     }
 
     fn total_load(&self) -> usize {
@@ -159,10 +186,16 @@ impl Platform {
 }
 
 const TARGET_CYCLE: usize = 1000000000;
+const MAX_CYCLE_SEARCH: usize = 10000;
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let mut platform = Platform::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let mut platform = Platform::from_input(input);
+        let mut animation = options.visualize.then(|| Animation::new(1));
 
         platform.tilt_north();
 
@@ -172,8 +205,8 @@ impl Day for Instance {
         let mut cache = HashMap::new();
         let mut cycle_length = 0;
 
-        for i in 0.. {
-            platform.cycle();
+        for i in 0..MAX_CYCLE_SEARCH {
+            platform.cycle(animation.as_mut());
             cycles.push(platform.total_load());
             let rocks = platform
                 .rocks
@@ -183,13 +216,20 @@ impl Day for Instance {
                 .collect_vec();
 
             if let Some(j) = cache.get(&rocks) {
-                cycle_length = (i - j) as usize;
+                cycle_length = i - j;
                 break;
             } else {
                 cache.insert(rocks, i);
             }
         }
 
+        if cycle_length == 0 {
+            return Err(format!(
+                "Platform did not repeat within {} spin cycles",
+                MAX_CYCLE_SEARCH
+            ));
+        }
+
         let cycle = &cycles[(cycles.len() - cycle_length)..cycles.len()];
         let offset = TARGET_CYCLE % cycle_length;
         let start_offset = cycles.len() % cycle_length + 1;
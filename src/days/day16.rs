@@ -5,7 +5,14 @@ use std::{
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        export::{Drawing, Rgb},
+        render::{Animation, Canvas, Color},
+    },
+};
 
 pub struct Instance;
 
@@ -135,11 +142,23 @@ impl Grid {
     }
 
     fn beam(&self, start: &(&'static Direction, (usize, usize))) -> usize {
+        self.energized(start, None).len()
+    }
+
+    fn energized(
+        &self,
+        start: &(&'static Direction, (usize, usize)),
+        mut animation: Option<&mut Animation>,
+    ) -> HashSet<(usize, usize)> {
         let mut visited = HashSet::new();
         visited.insert(*start);
         let mut beams = vec![*start];
 
         while let Some(beam) = beams.pop() {
+            if let Some(animation) = animation.as_deref_mut() {
+                animation.frame(|| self.canvas(&visited, &beams));
+            }
+
             if let Some(g) = self.entries.get(&beam.1) {
                 let next = match g {
                     GridEntry::Mirror(m) => vec![m.next(beam.0, &beam.1)],
@@ -164,9 +183,7 @@ impl Grid {
             }
         }
 
-        let distinct: HashSet<(usize, usize)> = HashSet::from_iter(visited.iter().map(|c| c.1));
-
-        distinct.len()
+        HashSet::from_iter(visited.iter().map(|c| c.1))
     }
 
     fn beam_all(&self) -> usize {
@@ -184,6 +201,59 @@ impl Grid {
         starts.iter().map(|s| self.beam(s)).max().unwrap()
     }
 
+    fn drawing(&self, energized: &HashSet<(usize, usize)>) -> Drawing {
+        let mut drawing = Drawing::new(
+            (1.0, 1.0),
+            (self.x_len as f64 + 1.0, self.y_len as f64 + 1.0),
+            Rgb::DARK,
+        );
+        for &(x, y) in energized {
+            drawing.rect(x as f64, y as f64, 1.0, 1.0, Rgb::YELLOW);
+        }
+        for (&(x, y), entry) in &self.entries {
+            let (x, y) = (x as f64, y as f64);
+            let line = match entry {
+                GridEntry::Mirror(Mirror::NorthEast) => [(x, y + 1.0), (x + 1.0, y)],
+                GridEntry::Mirror(Mirror::NorthWest) => [(x, y), (x + 1.0, y + 1.0)],
+                GridEntry::Splitter(Splitter::Vertical) => [(x + 0.5, y), (x + 0.5, y + 1.0)],
+                GridEntry::Splitter(Splitter::Horizontal) => [(x, y + 0.5), (x + 1.0, y + 0.5)],
+            };
+            drawing.polyline(line.to_vec(), Rgb::BLUE, 0.2);
+        }
+        drawing
+    }
+
+    fn canvas(
+        &self,
+        visited: &HashSet<(&Direction, (usize, usize))>,
+        beams: &[(&Direction, (usize, usize))],
+    ) -> Canvas {
+        let mut canvas = Canvas::new(self.x_len, self.y_len);
+        let energized: HashSet<_> = visited.iter().map(|v| v.1).collect();
+        for y in 1..=self.y_len {
+            for x in 1..=self.x_len {
+                let c = match self.entries.get(&(x, y)) {
+                    Some(GridEntry::Mirror(Mirror::NorthEast)) => '/',
+                    Some(GridEntry::Mirror(Mirror::NorthWest)) => '\\',
+                    Some(GridEntry::Splitter(Splitter::Vertical)) => '|',
+                    Some(GridEntry::Splitter(Splitter::Horizontal)) => '-',
+                    None if energized.contains(&(x, y)) => '#',
+                    None => '.',
+                };
+                let color = if energized.contains(&(x, y)) {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                };
+                canvas.set(x - 1, y - 1, c, Some(color));
+            }
+        }
+        for (_, (x, y)) in beams {
+            canvas.set(x - 1, y - 1, '*', Some(Color::Red));
+        }
+        canvas
+    }
+
     fn contains(&self, c: &(usize, usize)) -> bool {
         c.0 > 0 && c.1 > 0 && c.0 <= self.x_len && c.1 <= self.y_len
     }
@@ -191,9 +261,18 @@ impl Grid {
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let grid = Grid::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let part1 = grid.beam(&(&Direction::Right, (1, 1))).to_string();
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let grid = Grid::from_lines(&input.to_lines());
+
+        let mut animation = options.visualize.then(|| Animation::new(10));
+        let energized = grid.energized(&(&Direction::Right, (1, 1)), animation.as_mut());
+        if let Some(path) = &options.export {
+            grid.drawing(&energized).save(path)?;
+        }
+        let part1 = energized.len().to_string();
         let part2 = grid.beam_all().to_string();
 
         Ok(DayResult {
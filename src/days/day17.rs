@@ -1,9 +1,13 @@
 use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap, HashSet},
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
 };
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::render::{Animation, Canvas, Color},
+};
 
 pub struct Instance;
 
@@ -37,7 +41,7 @@ impl LavaPool {
 }
 
 impl LavaPool {
-    fn min_heat(&self, min: u8, max: u8) -> u32 {
+    fn min_heat(&self, min: u8, max: u8) -> (u32, Vec<(usize, usize)>) {
         let start = (1, 1);
 
         #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -71,21 +75,30 @@ impl LavaPool {
         struct State(u32, u8, (usize, usize), &'static Direction);
 
         let mut heap: BinaryHeap<Reverse<State>> = BinaryHeap::new();
-        let mut seen = HashSet::new();
+        // Each state remembers the state it was first reached from to recover the path
+        let mut seen = HashMap::new();
 
         heap.push(Reverse(State(0, 0, start, &Right)));
         heap.push(Reverse(State(0, 0, start, &Down)));
 
         while let Some(Reverse(State(heat, steps, coord, direction))) = heap.pop() {
             if coord == (self.x_len, self.y_len) {
-                return heat;
+                let mut path = vec![coord];
+                let mut key = (steps, coord, direction);
+                while let Some(&previous) = seen.get(&key) {
+                    key = previous;
+                    path.push(key.1);
+                }
+                path.reverse();
+                return (heat, path);
             }
 
             if steps + 1 < max {
                 let next = direction.next(&coord);
                 if let Some(next_heat) = self.cells.get(&next) {
                     let state = State(heat + next_heat, steps + 1, next, direction);
-                    if seen.insert((state.1, state.2, state.3)) {
+                    if let Entry::Vacant(e) = seen.entry((state.1, state.2, state.3)) {
+                        e.insert((steps, coord, direction));
                         heap.push(Reverse(state));
                     }
                 }
@@ -97,7 +110,8 @@ impl LavaPool {
                 let next = a.next(&coord);
                 if let Some(next_heat) = self.cells.get(&next) {
                     let state = State(heat + next_heat, 0, next, a);
-                    if seen.insert((state.1, state.2, state.3)) {
+                    if let Entry::Vacant(e) = seen.entry((state.1, state.2, state.3)) {
+                        e.insert((steps, coord, direction));
                         heap.push(Reverse(state));
                     }
                 }
@@ -105,7 +119,8 @@ impl LavaPool {
                 let next = b.next(&coord);
                 if let Some(next_heat) = self.cells.get(&next) {
                     let state = State(heat + next_heat, 0, next, b);
-                    if seen.insert((state.1, state.2, state.3)) {
+                    if let Entry::Vacant(e) = seen.entry((state.1, state.2, state.3)) {
+                        e.insert((steps, coord, direction));
                         heap.push(Reverse(state));
                     }
                 }
@@ -115,21 +130,52 @@ impl LavaPool {
         panic!("Didn't find path")
     }
 
-    fn min_heat_basic(&self) -> u32 {
+    fn min_heat_basic(&self) -> (u32, Vec<(usize, usize)>) {
         self.min_heat(0, 3)
     }
 
-    fn min_heat_ultra(&self) -> u32 {
+    fn min_heat_ultra(&self) -> (u32, Vec<(usize, usize)>) {
         self.min_heat(4, 10)
     }
+
+    fn canvas(&self, path: &[(usize, usize)]) -> Canvas {
+        let mut canvas = Canvas::new(self.x_len, self.y_len);
+        for (&(x, y), heat) in &self.cells {
+            let c = char::from_digit(*heat, 10).unwrap();
+            canvas.set(x - 1, y - 1, c, Some(Color::Gray));
+        }
+        for (&(x0, y0), &(x, y)) in path.iter().zip(&path[1..]) {
+            let c = match (x.cmp(&x0), y.cmp(&y0)) {
+                (Ordering::Greater, _) => '>',
+                (Ordering::Less, _) => '<',
+                (_, Ordering::Greater) => 'v',
+                _ => '^',
+            };
+            canvas.set(x - 1, y - 1, c, Some(Color::Red));
+        }
+        canvas
+    }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let pool = LavaPool::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let pool = LavaPool::from_lines(&input.to_lines());
+
+        let (part1, basic_path) = pool.min_heat_basic();
+        let (part2, ultra_path) = pool.min_heat_ultra();
+
+        if options.visualize {
+            let animation = Animation::new(1);
+            animation.show(&pool.canvas(&basic_path));
+            animation.show(&pool.canvas(&ultra_path));
+        }
 
-        let part1: String = pool.min_heat_basic().to_string();
-        let part2: String = pool.min_heat_ultra().to_string();
+        let part1 = part1.to_string();
+        let part2 = part2.to_string();
 
         Ok(DayResult {
             part1,
@@ -2,7 +2,14 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        export::{Drawing, Rgb},
+        polygon::Polygon,
+    },
+};
 
 pub struct Instance;
 
@@ -89,6 +96,7 @@ impl FromStr for HackedInstruction {
 
 struct SparseGrid {
     pos: (i32, i32),
+    corners: Vec<(i32, i32)>,
     hor_lines: Vec<(i32, i32, i32)>,
     ver_lines: Vec<(i32, i32, i32)>,
 }
@@ -97,6 +105,7 @@ impl SparseGrid {
     fn new() -> Self {
         SparseGrid {
             pos: (0, 0),
+            corners: Vec::new(),
             hor_lines: Vec::new(),
             ver_lines: Vec::new(),
         }
@@ -110,9 +119,48 @@ impl SparseGrid {
             Direction::Left => self.hor_lines.push((self.pos.1, next.0, self.pos.0)),
             Direction::Right => self.hor_lines.push((self.pos.1, self.pos.0, next.0)),
         }
+        self.corners.push(next);
         self.pos = next;
     }
 
+    fn polygon_volume(&self) -> Result<usize, String> {
+        let polygon = Polygon::new(
+            self.corners
+                .iter()
+                .map(|&(x, y)| (x as i64, y as i64))
+                .collect(),
+        );
+        Ok(polygon.lattice_points()? as usize)
+    }
+
+    fn drawing(&self) -> Result<Drawing, String> {
+        if self.corners.is_empty() {
+            return Err("No trench to draw".to_owned());
+        }
+
+        let xs = self.corners.iter().map(|c| c.0 as f64);
+        let ys = self.corners.iter().map(|c| c.1 as f64);
+        let min = (
+            xs.clone().fold(f64::MAX, f64::min),
+            ys.clone().fold(f64::MAX, f64::min),
+        );
+        let max = (xs.fold(f64::MIN, f64::max), ys.fold(f64::MIN, f64::max));
+
+        // Trench cells are centred on the corner coordinates
+        let centres = self
+            .corners
+            .iter()
+            .map(|&(x, y)| (x as f64 + 0.5, y as f64 + 0.5))
+            .collect_vec();
+        let mut trench = centres.clone();
+        trench.push(centres[0]);
+
+        let mut drawing = Drawing::new(min, (max.0 + 1.0, max.1 + 1.0), Rgb::DARK);
+        drawing.polygon(centres, Rgb::YELLOW);
+        drawing.polyline(trench, Rgb::RED, 1.0);
+        Ok(drawing)
+    }
+
     fn volume(&mut self) -> usize {
         #[derive(Debug)]
         enum Corner {
@@ -195,10 +243,42 @@ impl SparseGrid {
 
         volume
     }
+
+    fn lagoon_size(&mut self, solver: LagoonSolver) -> Result<usize, String> {
+        match solver {
+            LagoonSolver::Scanline => Ok(self.volume()),
+            LagoonSolver::Polygon => self.polygon_volume(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LagoonSolver {
+    // The original solution, kept to check the polygon one against
+    Scanline,
+    Polygon,
+}
+
+impl FromStr for LagoonSolver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scanline" => Ok(LagoonSolver::Scanline),
+            "polygon" => Ok(LagoonSolver::Polygon),
+            _ => Err(format!("Unknown lagoon solver: {}", s)),
+        }
+    }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let solver: LagoonSolver = options.param("lagoon").unwrap_or("polygon").parse()?;
+        let lines = input.to_lines();
         let instructions: Vec<Instruction> = lines.iter().map(|l| l.parse()).try_collect()?;
         let hacked_instructions: Vec<HackedInstruction> =
             lines.iter().map(|l| l.parse()).try_collect()?;
@@ -207,13 +287,16 @@ impl Day for Instance {
         for instruction in instructions.iter() {
             sparse_grid.apply(instruction);
         }
-        let part1 = sparse_grid.volume().to_string();
+        let part1 = sparse_grid.lagoon_size(solver)?.to_string();
+        if let Some(path) = &options.export {
+            sparse_grid.drawing()?.save(path)?;
+        }
 
         let mut sparse_grid = SparseGrid::new();
         for HackedInstruction(instruction) in hacked_instructions.iter() {
             sparse_grid.apply(instruction);
         }
-        let part2 = sparse_grid.volume().to_string();
+        let part2 = sparse_grid.lagoon_size(solver)?.to_string();
 
         Ok(DayResult {
             part1,
@@ -221,3 +304,23 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lagoon_solvers_agree() {
+        let input = Input::read("fixtures/day18.txt").unwrap();
+        for solver in ["scanline", "polygon"] {
+            let options = RunOptions {
+                params: vec![("lagoon".to_owned(), solver.to_owned())],
+                ..RunOptions::default()
+            };
+            let result = Instance.run_input(&input, &options).unwrap();
+
+            assert_eq!(result.part1, "62");
+            assert_eq!(result.part2.as_deref(), Some("952408144115"));
+        }
+    }
+}
@@ -6,7 +6,14 @@ use std::{
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        graph::Graph,
+        interval::{HyperRect, Interval},
+    },
+};
 
 pub struct Instance;
 
@@ -59,15 +66,6 @@ impl Part {
         }
     }
 
-    fn set(&mut self, p: &Property, v: u16) {
-        match p {
-            Property::X => self.x = v,
-            Property::M => self.m = v,
-            Property::A => self.a = v,
-            Property::S => self.s = v,
-        }
-    }
-
     fn rating(&self) -> u32 {
         (self.x + self.m + self.a + self.s) as u32
     }
@@ -80,6 +78,16 @@ enum Outcome {
     Workflow(String),
 }
 
+impl Outcome {
+    fn name(&self) -> &str {
+        match self {
+            Outcome::Accept => "A",
+            Outcome::Reject => "R",
+            Outcome::Workflow(w) => w,
+        }
+    }
+}
+
 impl FromStr for Outcome {
     type Err = String;
 
@@ -100,6 +108,26 @@ enum Property {
     S,
 }
 
+impl Property {
+    fn name(&self) -> char {
+        match self {
+            Property::X => 'x',
+            Property::M => 'm',
+            Property::A => 'a',
+            Property::S => 's',
+        }
+    }
+
+    fn axis(&self) -> usize {
+        match self {
+            Property::X => 0,
+            Property::M => 1,
+            Property::A => 2,
+            Property::S => 3,
+        }
+    }
+}
+
 impl FromStr for Property {
     type Err = String;
 
@@ -120,9 +148,24 @@ enum Rule {
     Lt(Property, u16, Outcome),
 }
 
-type MatchedUnmatched = (Option<(Part, Part)>, Option<(Part, Part)>);
+type Bounds = HyperRect<4>;
+
+type MatchedUnmatched = (Option<Bounds>, Option<Bounds>);
 
 impl Rule {
+    fn outcome(&self) -> &Outcome {
+        match self {
+            Rule::Gt(_, _, o) | Rule::Lt(_, _, o) => o,
+        }
+    }
+
+    fn condition(&self) -> String {
+        match self {
+            Rule::Gt(p, v, _) => format!("{}>{}", p.name(), v),
+            Rule::Lt(p, v, _) => format!("{}<{}", p.name(), v),
+        }
+    }
+
     fn apply(&self, part: &Part) -> Option<&Outcome> {
         match self {
             Rule::Gt(p, v, o) => (part.get(p) > *v).then_some(o),
@@ -130,40 +173,13 @@ impl Rule {
         }
     }
 
-    fn matched_unmatched(&self, from: &Part, to: &Part) -> MatchedUnmatched {
+    fn matched_unmatched(&self, bounds: &Bounds) -> MatchedUnmatched {
         match self {
             Rule::Gt(p, v, _) => {
-                if from.get(p) > *v {
-                    return (Some((from.clone(), to.clone())), None);
-                } else if to.get(p) <= *v {
-                    return (None, Some((from.clone(), to.clone())));
-                }
-                let mut higher_from = from.clone();
-                higher_from.set(p, v + 1);
-                let mut lower_to = to.clone();
-                lower_to.set(p, *v);
-
-                (
-                    Some((higher_from, to.clone())),
-                    Some((from.clone(), lower_to)),
-                )
-            }
-            Rule::Lt(p, v, _) => {
-                if to.get(p) < *v {
-                    return (Some((from.clone(), to.clone())), None);
-                } else if from.get(p) >= *v {
-                    return (None, Some((from.clone(), to.clone())));
-                }
-                let mut lower_to = to.clone();
-                lower_to.set(p, v - 1);
-                let mut higher_from = from.clone();
-                higher_from.set(p, *v);
-
-                (
-                    Some((from.clone(), lower_to)),
-                    Some((higher_from, to.clone())),
-                )
+                let (lower, higher) = bounds.split(p.axis(), *v as i64 + 1);
+                (higher, lower)
             }
+            Rule::Lt(p, v, _) => bounds.split(p.axis(), *v as i64),
         }
     }
 }
@@ -257,6 +273,23 @@ impl<'a> Workflows<'a> {
 }
 
 impl Workflows<'_> {
+    fn graph(&self) -> Graph {
+        let mut graph = Graph::new(true);
+        graph.node("A").kind("accept");
+        graph.node("R").kind("reject");
+        for (name, workflow) in &self.0 {
+            let kind = if *name == "in" { "start" } else { "workflow" };
+            graph.node(name).kind(kind);
+            for rule in &workflow.rules {
+                graph
+                    .edge(name, rule.outcome().name())
+                    .label(&rule.condition());
+            }
+            graph.edge(name, workflow.default.name()).label("else");
+        }
+        graph
+    }
+
     fn accepts(&self, part: &Part) -> bool {
         let mut workflow = self.0["in"];
 
@@ -270,64 +303,37 @@ impl Workflows<'_> {
     }
 
     fn total_accepted(&self) -> u64 {
-        let mut candidates = vec![(
-            Part {
-                x: 1,
-                m: 1,
-                s: 1,
-                a: 1,
-            },
-            Part {
-                x: 4000,
-                m: 4000,
-                s: 4000,
-                a: 4000,
-            },
-            self.0["in"],
-        )];
+        let mut candidates = vec![(HyperRect([Interval::from_bounds(1, 4001); 4]), self.0["in"])];
 
         let mut accepted = 0;
 
         fn run_outcome<'a>(
             outcome: &Outcome,
-            from: Part,
-            to: Part,
-            candidates: &mut Vec<(Part, Part, &'a Workflow)>,
+            bounds: Bounds,
+            candidates: &mut Vec<(Bounds, &'a Workflow)>,
             accepted: &mut u64,
             workflows: &'a Workflows,
         ) {
             match outcome {
-                Outcome::Accept => {
-                    *accepted += (to.x - from.x + 1) as u64
-                        * (to.m - from.m + 1) as u64
-                        * (to.s - from.s + 1) as u64
-                        * (to.a - from.a + 1) as u64;
-                }
+                Outcome::Accept => *accepted += bounds.volume() as u64,
                 Outcome::Reject => (),
-                Outcome::Workflow(w) => candidates.push((from, to, workflows.0[w.as_str()])),
+                Outcome::Workflow(w) => candidates.push((bounds, workflows.0[w.as_str()])),
             }
         }
 
-        'outer: while let Some((from, to, workflow)) = candidates.pop() {
-            let mut from = from;
-            let mut to = to;
+        'outer: while let Some((bounds, workflow)) = candidates.pop() {
+            let mut bounds = bounds;
             for rule in &workflow.rules {
                 match rule {
                     Rule::Gt(_, _, o) | Rule::Lt(_, _, o) => {
-                        let (matched, unmatched) = rule.matched_unmatched(&from, &to);
+                        let (matched, unmatched) = rule.matched_unmatched(&bounds);
 
-                        match matched {
-                            Some((f, t)) => {
-                                run_outcome(o, f, t, &mut candidates, &mut accepted, self)
-                            }
-                            None => continue,
+                        if let Some(m) = matched {
+                            run_outcome(o, m, &mut candidates, &mut accepted, self)
                         }
 
                         match unmatched {
-                            Some((f, t)) => {
-                                from = f;
-                                to = t
-                            }
+                            Some(u) => bounds = u,
                             None => continue 'outer,
                         }
                     }
@@ -336,8 +342,7 @@ impl Workflows<'_> {
 
             run_outcome(
                 &workflow.default,
-                from,
-                to,
+                bounds,
                 &mut candidates,
                 &mut accepted,
                 self,
@@ -350,6 +355,11 @@ impl Workflows<'_> {
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
         let workflows = lines
             .iter()
             .filter_map(|l| l.parse::<Workflow>().ok())
@@ -361,6 +371,9 @@ impl Day for Instance {
             .collect_vec();
 
         let workflows = Workflows::from_slice(&workflows);
+        if let Some(path) = &options.graph {
+            workflows.graph().save(path)?;
+        }
 
         let mut part1 = 0;
         for part in parts {
@@ -1,71 +1,103 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+};
 
 pub struct Instance;
 
-struct Handful {
-    red: u8,
-    green: u8,
-    blue: u8,
-}
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+// Cube counts per colour, a colour that is missing counts as zero
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Cubes(BTreeMap<String, u32>);
 
-impl Handful {
-    fn is_possible(&self, total_red: u8, total_green: u8, total_blue: u8) -> bool {
-        total_red >= self.red && total_green >= self.green && total_blue >= self.blue
+impl Cubes {
+    fn get(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
     }
-}
 
-struct Game {
-    id: u8,
-    handfuls: Vec<Handful>,
+    fn fits_in(&self, bag: &Cubes) -> bool {
+        self.0.iter().all(|(colour, &n)| n <= bag.get(colour))
+    }
+
+    fn max(mut self, other: &Cubes) -> Cubes {
+        for (colour, &n) in &other.0 {
+            let entry = self.0.entry(colour.clone()).or_default();
+            *entry = (*entry).max(n);
+        }
+        self
+    }
+
+    fn with(&self, changes: &Cubes) -> Cubes {
+        let mut cubes = self.clone();
+        cubes
+            .0
+            .extend(changes.0.iter().map(|(c, &n)| (c.clone(), n)));
+        cubes
+    }
+
+    fn power(&self) -> Result<u64, String> {
+        self.0
+            .values()
+            .try_fold(1u64, |power, &n| power.checked_mul(n as u64))
+            .ok_or(format!("Overflow taking the power of {}", self))
+    }
 }
 
-impl FromStr for Handful {
+impl FromStr for Cubes {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-
-        for colour_amount in s.split(", ") {
-            let mut split = colour_amount.split(' ');
-            let amount = split
-                .next()
-                .ok_or("missing amount")?
-                .parse::<u8>()
-                .map_err(|e| e.to_string())?;
-            let colour = split.next().ok_or("missing colour")?;
-
-            match colour {
-                "red" => red = amount,
-                "green" => green = amount,
-                "blue" => blue = amount,
-                other => Err(format!("unknown colour {}", other))?,
-            }
+        let mut cubes = BTreeMap::new();
+
+        for colour_amount in s.split(',').map(|c| c.trim()) {
+            let (amount, colour) = colour_amount
+                .split_once(' ')
+                .ok_or(format!("missing colour in {:?}", colour_amount))?;
+            let amount = amount.parse::<u32>().map_err(|e| e.to_string())?;
+
+            let total: &mut u32 = cubes.entry(colour.to_owned()).or_default();
+            *total = total
+                .checked_add(amount)
+                .ok_or(format!("Overflow counting {} cubes", colour))?;
         }
 
-        Ok(Handful { red, green, blue })
+        Ok(Cubes(cubes))
+    }
+}
+
+impl Display for Cubes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<_> = self
+            .0
+            .iter()
+            .map(|(colour, n)| format!("{} {}", n, colour))
+            .collect();
+        write!(f, "{}", parts.join(", "))
     }
 }
 
+struct Game {
+    id: u32,
+    handfuls: Vec<Cubes>,
+}
+
 impl FromStr for Game {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(": ");
-
-        let game = split.next().unwrap();
+        let (game, handfuls) = s.split_once(": ").ok_or("missing handfuls")?;
 
-        let id = game[5..game.len()]
-            .parse::<u8>()
+        let id = game
+            .strip_prefix("Game ")
+            .ok_or(format!("bad game {:?}", game))?
+            .parse::<u32>()
             .map_err(|e| e.to_string())?;
 
-        let handfuls = split
-            .next()
-            .ok_or("missing handfuls")?
+        let handfuls = handfuls
             .split("; ")
-            .map(|s| s.parse::<Handful>())
+            .map(|s| s.parse::<Cubes>())
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(Game { id, handfuls })
@@ -73,44 +105,196 @@ impl FromStr for Game {
 }
 
 impl Game {
-    fn power(&self) -> u32 {
-        let (r, g, b) = self.handfuls.iter().fold((0, 0, 0), |(r, g, b), c| {
-            (
-                r.max(c.red as u32),
-                g.max(c.green as u32),
-                b.max(c.blue as u32),
-            )
-        });
-        r * g * b
+    fn is_possible(&self, bag: &Cubes) -> bool {
+        self.handfuls.iter().all(|h| h.fits_in(bag))
+    }
+
+    // Colours are seeded from the whole input so a colour never drawn in this game powers to 0
+    fn minimal_bag(&self, colours: &Cubes) -> Cubes {
+        self.handfuls.iter().fold(colours.clone(), Cubes::max)
     }
 }
 
+fn possible_ids(games: &[Game], bag: &Cubes) -> Vec<u32> {
+    games
+        .iter()
+        .filter(|g| g.is_possible(bag))
+        .map(|g| g.id)
+        .collect()
+}
+
+// Games that become possible and impossible once the bag changes
+fn what_if(games: &[Game], bag: &Cubes, changes: &Cubes) -> (Vec<u32>, Vec<u32>) {
+    let changed = bag.with(changes);
+    let gained = games
+        .iter()
+        .filter(|g| !g.is_possible(bag) && g.is_possible(&changed))
+        .map(|g| g.id)
+        .collect();
+    let lost = games
+        .iter()
+        .filter(|g| g.is_possible(bag) && !g.is_possible(&changed))
+        .map(|g| g.id)
+        .collect();
+    (gained, lost)
+}
+
+// Every colour drawn in any game, each with a count of zero
+fn colours(games: &[Game]) -> Cubes {
+    Cubes(
+        games
+            .iter()
+            .flat_map(|g| &g.handfuls)
+            .flat_map(|h| h.0.keys())
+            .map(|c| (c.clone(), 0))
+            .collect(),
+    )
+}
+
+// The smallest bag for one game by id, or for every game at once with `all`
+fn minimal_bag_query(games: &[Game], which: &str) -> Result<Cubes, String> {
+    let colours = colours(games);
+    if which == "all" {
+        return Ok(games
+            .iter()
+            .map(|g| g.minimal_bag(&colours))
+            .fold(colours.clone(), |a, b| a.max(&b)));
+    }
+
+    let id = which
+        .parse::<u32>()
+        .map_err(|e| format!("{}: {}", e, which))?;
+    games
+        .iter()
+        .find(|g| g.id == id)
+        .map(|g| g.minimal_bag(&colours))
+        .ok_or(format!("No game {}", id))
+}
+
+fn parse(input: &Input, options: &RunOptions) -> Result<(Cubes, Vec<Game>), String> {
+    let bag = options.param("bag").unwrap_or(DEFAULT_BAG).parse()?;
+    let games = input
+        .lines()
+        .map(|s| s.parse::<Game>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((bag, games))
+}
+
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let target_red = 12;
-        let target_green = 13;
-        let target_blue = 14;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let games = lines
-            .iter()
-            .map(|s| s.parse::<Game>())
-            .collect::<Result<Vec<_>, _>>()?;
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let (bag, games) = parse(input, options)?;
 
-        let part1 = games
+        let part1 = possible_ids(&games, &bag)
             .iter()
-            .filter(|g| {
-                g.handfuls
-                    .iter()
-                    .all(|h| h.is_possible(target_red, target_green, target_blue))
-            })
-            .map(|g| g.id as u16)
-            .sum::<u16>();
+            .try_fold(0u32, |sum, &id| sum.checked_add(id))
+            .ok_or("Overflow summing game ids")?;
 
-        let part2 = games.iter().map(|g| g.power()).sum::<u32>();
+        let colours = colours(&games);
+        let part2 = games.iter().try_fold(0u64, |sum, g| {
+            sum.checked_add(g.minimal_bag(&colours).power()?)
+                .ok_or("Overflow summing powers".to_owned())
+        })?;
 
         Ok(DayResult {
             part1: part1.to_string(),
             part2: Some(part2.to_string()),
         })
     }
+
+    // `what-if=<cubes>` changes the bag, `minimal-bag=<id>` or `minimal-bag=all` sizes one
+    fn queries(
+        &self,
+        input: &Input,
+        options: &RunOptions,
+    ) -> Result<Vec<(String, String)>, String> {
+        let (bag, games) = parse(input, options)?;
+        let mut answers = Vec::new();
+
+        if let Some(changes) = options.param("what-if") {
+            let changes: Cubes = changes.parse()?;
+            let (gained, lost) = what_if(&games, &bag, &changes);
+            answers.push((
+                format!("With {}", bag.with(&changes)),
+                format!("gains {:?}, loses {:?}", gained, lost),
+            ));
+        }
+        if let Some(which) = options.param("minimal-bag") {
+            answers.push((
+                format!("Minimal bag for {}", which),
+                minimal_bag_query(&games, which)?.to_string(),
+            ));
+        }
+
+        Ok(answers)
+    }
+
+    fn version(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        let input: Input = [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>()
+        .into();
+        let options = RunOptions {
+            params: params
+                .iter()
+                .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+            ..RunOptions::default()
+        };
+        Instance.queries(&input, &options).unwrap()
+    }
+
+    #[test]
+    fn answers_what_if() {
+        let answers = ask(&[("what-if", "20 red")]);
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].0, "With 14 blue, 13 green, 20 red");
+        assert_eq!(answers[0].1, "gains [3], loses []");
+
+        let answers = ask(&[("what-if", "2 blue")]);
+        assert_eq!(answers[0].1, "gains [], loses [1, 2]");
+    }
+
+    #[test]
+    fn answers_minimal_bags() {
+        let answers = ask(&[("minimal-bag", "2")]);
+        assert_eq!(answers[0].1, "4 blue, 3 green, 1 red");
+
+        let answers = ask(&[("minimal-bag", "all")]);
+        assert_eq!(answers[0].1, "6 blue, 13 green, 20 red");
+
+        assert!(ask(&[]).is_empty());
+    }
+
+    #[test]
+    fn reports_overflow() {
+        let run = |line: &str| Instance.run(vec![line.to_owned()]);
+
+        assert!(run("Game 1: 4294967295 red, 1 red").is_err());
+        assert!(run("Game 1: 4294967295 red, 4294967295 blue, 2 green").is_err());
+        assert_eq!(
+            run("Game 1: 4294967295 red, 4294967295 blue")
+                .unwrap()
+                .part2
+                .as_deref(),
+            Some((u32::MAX as u64 * u32::MAX as u64).to_string().as_str())
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     iter,
     str::FromStr,
 };
@@ -7,12 +7,18 @@ use std::{
 use itertools::Itertools;
 
 use super::{
-    day::{Day, DayResult},
-    util::lcm,
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{graph::Graph, lcm},
 };
 
 pub struct Instance;
 
+// Real inputs have periods of a few thousand presses
+const MAX_ANALYZED_PRESSES: usize = 100_000;
+// Beyond this the reference presses take too long to be useful
+const MAX_REFERENCE_PRESSES: usize = 1_000_000;
+
 #[derive(Debug)]
 enum ModuleType {
     FlipFlop,
@@ -51,6 +57,34 @@ impl FromStr for Module {
     }
 }
 
+impl Module {
+    fn kind(&self) -> &'static str {
+        match self.module_type {
+            ModuleType::FlipFlop => "flip-flop",
+            ModuleType::Conjunction => "conjunction",
+            ModuleType::Broadcast => "broadcaster",
+        }
+    }
+}
+
+// Highlights the conjunction feeding rx along with its inputs
+fn network_graph(modules: &[Module], feeder: Option<&str>) -> Graph {
+    let mut graph = Graph::new(true);
+    for m in modules {
+        graph.node(&m.source).kind(m.kind());
+        for d in &m.destinations {
+            let edge = graph.edge(&m.source, d);
+            if Some(d.as_str()) == feeder {
+                edge.highlight();
+            }
+        }
+    }
+    if let Some(feeder) = feeder {
+        graph.highlight_path(&[feeder, "rx"]);
+    }
+    graph
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum Pulse {
     High,
@@ -150,18 +184,54 @@ impl Circuit<'_> {
         ret
     }
 
-    fn analyze(&mut self) -> usize {
-        let target = self
+    fn rx_feeder(&self) -> Result<Option<&str>, String> {
+        let rx = "rx".to_owned();
+        let conjunctions = self
             .conjunctions
             .iter()
-            .find(|m| m.1 .1.contains(&"rx".to_string()))
-            .unwrap();
-
-        let name = target.0.to_owned();
+            .filter(|(_, (_, outputs))| outputs.contains(&rx))
+            .map(|(name, _)| *name)
+            .collect_vec();
+        let others = self
+            .flip_flops
+            .iter()
+            .filter(|(_, (_, outputs))| outputs.contains(&rx))
+            .count()
+            + self.broadcaster.contains(&rx) as usize;
+
+        match (conjunctions.as_slice(), others) {
+            ([], 0) => Err("No module sends pulses to rx".to_owned()),
+            ([name], 0) => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
 
+    // Finds when every input of the conjunction feeding rx fires high at once, assuming each
+    // input fires on exact multiples of its own period
+    fn analyze(&mut self, name: &str) -> Result<usize, String> {
+        let inputs: HashSet<&str> = self
+            .conjunctions
+            .get(name)
+            .ok_or(format!("{} is not a conjunction", name))?
+            .0
+            .keys()
+            .copied()
+            .collect();
         let mut cycles = HashMap::new();
 
         loop {
+            if self.button_pushes >= MAX_ANALYZED_PRESSES {
+                let silent = inputs
+                    .iter()
+                    .filter(|i| cycles.get(**i).is_none_or(|v: &Vec<usize>| v.len() < 2))
+                    .sorted()
+                    .join(", ");
+                return Err(format!(
+                    "{} did not fire high twice within {} presses",
+                    silent, MAX_ANALYZED_PRESSES
+                ));
+            }
+
             let x = self.push_the_button();
 
             let pulses = x
@@ -177,7 +247,7 @@ impl Circuit<'_> {
                     .or_insert(vec![self.button_pushes]);
             }
 
-            if !cycles.is_empty() && cycles.values().all(|v| v.len() > 1) {
+            if cycles.len() == inputs.len() && cycles.values().all(|v| v.len() > 1) {
                 break;
             }
         }
@@ -187,25 +257,66 @@ impl Circuit<'_> {
         for v in cycles.values() {
             let d = v.iter().tuple_windows().map(|(a, b)| b - a).collect_vec();
 
+            // Each input has to fire on exact multiples of its period for lcm to hold
+            if v[0] % d[0] != 0 {
+                return Err(format!(
+                    "An input of {} first fires at {} which is not a multiple of its period {}",
+                    name, v[0], d[0]
+                ));
+            }
+
             r = lcm(r, d[0]);
         }
 
-        r
+        Ok(r)
+    }
+
+    fn presses_until_rx(&mut self) -> Result<usize, String> {
+        self.rx_feeder()?;
+        while self.button_pushes < MAX_REFERENCE_PRESSES {
+            let pulses = self.push_the_button();
+            if pulses
+                .iter()
+                .any(|(target, _, pulse)| *target == "rx" && *pulse == Pulse::Low)
+            {
+                return Ok(self.button_pushes);
+            }
+        }
+        Err(format!(
+            "rx got no low pulse in {} presses, the reference stops there",
+            MAX_REFERENCE_PRESSES
+        ))
     }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let modules: Vec<_> = lines.iter().map(|l| l.parse::<Module>()).try_collect()?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let modules: Vec<_> = input.lines().map(|l| l.parse::<Module>()).try_collect()?;
 
         let mut circuit = Circuit::from_modules(&modules);
+        if let Some(path) = &options.graph {
+            network_graph(&modules, circuit.rx_feeder().ok().flatten()).save(path)?;
+        }
 
         for _ in 0..1000 {
             circuit.push_the_button();
         }
 
         let part1 = circuit.low_signals * circuit.high_signals;
-        let part2 = circuit.analyze();
+        let part2 = if options.reference {
+            Circuit::from_modules(&modules).presses_until_rx()?
+        } else {
+            // The cycle analysis relies on a single conjunction feeding rx
+            let feeder = circuit
+                .rx_feeder()?
+                .ok_or("rx is not fed by a single conjunction, try --reference")?
+                .to_owned();
+            circuit.analyze(&feeder)?
+        };
 
         Ok(DayResult {
             part1: part1.to_string(),
@@ -213,3 +324,71 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_matches_fast_path() {
+        // hub hears from inverters that fire high every press, every 2nd and every 4th
+        let modules: Vec<Module> = [
+            "broadcaster -> a, ia",
+            "%a -> na, b",
+            "%b -> nb",
+            "&ia -> hub",
+            "&na -> hub",
+            "&nb -> hub",
+            "&hub -> rx",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+
+        let mut circuit = Circuit::from_modules(&modules);
+        assert_eq!(circuit.rx_feeder(), Ok(Some("hub")));
+        assert_eq!(circuit.analyze("hub"), Ok(4));
+        assert_eq!(Circuit::from_modules(&modules).presses_until_rx(), Ok(4));
+    }
+
+    #[test]
+    fn analysis_waits_for_every_input() {
+        // nc only ever sends low pulses, so hub never fires and rx is never reached
+        let modules: Vec<Module> = [
+            "broadcaster -> a, ia",
+            "%a -> na",
+            "&ia -> hub, nc",
+            "&na -> hub",
+            "&nc -> hub",
+            "&hub -> rx",
+        ]
+        .iter()
+        .map(|l| l.parse().unwrap())
+        .collect();
+
+        let error = Circuit::from_modules(&modules).analyze("hub").unwrap_err();
+        assert!(error.starts_with("nc did not fire"), "{}", error);
+    }
+
+    #[test]
+    fn reference_stops() {
+        let modules: Vec<Module> = ["broadcaster -> a", "%a -> b", "&b -> a"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        let error = Circuit::from_modules(&modules)
+            .presses_until_rx()
+            .unwrap_err();
+        assert_eq!(error, "No module sends pulses to rx");
+
+        // rx is wired up but hub never sends it a low pulse
+        let modules: Vec<Module> = ["broadcaster -> hub", "&hub -> rx"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        let error = Circuit::from_modules(&modules)
+            .presses_until_rx()
+            .unwrap_err();
+        assert!(error.starts_with("rx got no low pulse"), "{}", error);
+    }
+}
@@ -1,6 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        poly::Polynomial,
+        render::{Animation, Canvas, Color},
+    },
+};
 
 pub struct Instance;
 
@@ -13,6 +20,8 @@ struct Garden {
 }
 
 const TARGET: i64 = 26501365;
+// Beyond this the reference walk takes too long to be useful
+const MAX_WALKED_STEPS: i64 = 1000;
 
 impl Garden {
     fn from_lines(lines: &[String]) -> Self {
@@ -40,7 +49,26 @@ impl Garden {
         }
     }
 
-    fn steps_64(&self) -> usize {
+    fn canvas(&self, possibilities: &HashSet<(i32, i32)>) -> Canvas {
+        let mut canvas = Canvas::new(self.x_len as usize, self.y_len as usize);
+        for y in 0..self.y_len {
+            for x in 0..self.x_len {
+                let (c, color) = if possibilities.contains(&(x, y)) {
+                    ('O', Color::Green)
+                } else if self.rocks.contains(&(x, y)) {
+                    ('#', Color::Gray)
+                } else if (x, y) == self.start {
+                    ('S', Color::Red)
+                } else {
+                    ('.', Color::Gray)
+                };
+                canvas.set(x as usize, y as usize, c, Some(color));
+            }
+        }
+        canvas
+    }
+
+    fn steps_64(&self, mut animation: Option<&mut Animation>) -> usize {
         let mut possibilities = HashSet::new();
         possibilities.insert(self.start);
         for _ in 0..64 {
@@ -64,23 +92,27 @@ impl Garden {
                 }
             }
             possibilities = new_possibilities;
+
+            if let Some(animation) = animation.as_deref_mut() {
+                animation.frame(|| self.canvas(&possibilities));
+            }
         }
 
         possibilities.len()
     }
 
-    fn steps_26501365(&self) -> i64 {
+    fn samples(&self, steps: i64, periods: i32) -> Vec<i64> {
         let mut possibilities = HashSet::new();
         possibilities.insert((self.start.0, self.start.1));
 
-        let diff = TARGET % self.x_len as i64;
+        let diff = (steps % self.x_len as i64) as i32;
 
         let mut xs = Vec::new();
 
-        for i in 0..=(diff as i32 + 2 * self.x_len) {
+        for i in 0..=(diff + (periods - 1) * self.x_len) {
             let mut new_possibilities = HashSet::new();
 
-            if i % self.x_len == diff as i32 {
+            if i % self.x_len == diff {
                 xs.push(possibilities.len() as i64);
             }
 
@@ -104,29 +136,96 @@ impl Garden {
             possibilities = new_possibilities;
         }
 
-        let x0 = xs[0];
-        let x1 = xs[1];
-        let x2 = xs[2];
+        xs
+    }
 
-        let d2_0 = x1 - x0;
-        let d2_1 = x2 - x1;
-        let d2 = (d2_1 - d2_0) / 2;
+    fn check_quadratic_growth(&self) -> Result<(), String> {
+        if self.x_len != self.y_len {
+            return Err(format!(
+                "Garden must be square for the quadratic fit, got {}x{}",
+                self.x_len, self.y_len
+            ));
+        }
+        if self.start != (self.x_len / 2, self.y_len / 2) {
+            return Err(format!(
+                "Start {:?} is not in the centre of the garden",
+                self.start
+            ));
+        }
+        if (0..self.x_len).any(|x| self.rocks.contains(&(x, self.start.1))) {
+            return Err("Row through the start is not clear".to_owned());
+        }
+        if (0..self.y_len).any(|y| self.rocks.contains(&(self.start.0, y))) {
+            return Err("Column through the start is not clear".to_owned());
+        }
+        Ok(())
+    }
+
+    fn steps_quadratic(&self, steps: i64) -> Result<i64, String> {
+        self.check_quadratic_growth()?;
+        Polynomial::fit(&self.samples(steps, 3), 2)?
+            .spaced(steps % self.x_len as i64, self.x_len as i64)?
+            .at(steps)
+    }
 
-        let d1 = x1 - d2 - x0;
+    // Walks the infinite garden breadth first, a plot is reachable in exactly `steps` steps
+    // when its distance is no more than that and has the same parity
+    fn steps_walked(&self, steps: i64) -> Result<i64, String> {
+        if steps > MAX_WALKED_STEPS {
+            return Err(format!(
+                "Walking {} steps is out of reach, the reference stops at {}",
+                steps, MAX_WALKED_STEPS
+            ));
+        }
 
-        let t = TARGET / self.x_len as i64;
+        let mut seen = HashSet::from([self.start]);
+        let mut queue = VecDeque::from([(self.start, 0)]);
+        let mut reachable = 0;
+        while let Some(((x, y), distance)) = queue.pop_front() {
+            if (steps - distance) % 2 == 0 {
+                reachable += 1;
+            }
+            if distance == steps {
+                continue;
+            }
+            for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                let rock = (next.0.rem_euclid(self.x_len), next.1.rem_euclid(self.y_len));
+                if !self.rocks.contains(&rock) && seen.insert(next) {
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
 
-        t * t * d2 + t * d1 + x0
+        Ok(reachable)
     }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let garden = Garden::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let part1 = garden.steps_64().to_string();
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let garden = Garden::from_lines(&input.to_lines());
 
-        let part2 = garden.steps_26501365().to_string();
+        let mut animation = options.visualize.then(|| Animation::new(1));
+        let part1 = garden.steps_64(animation.as_mut()).to_string();
+
+        let steps = match options.param("steps") {
+            Some(steps) => steps
+                .parse::<i64>()
+                .map_err(|e| format!("{}: {}", e, steps))?,
+            None => TARGET,
+        };
+        if steps < 0 {
+            return Err(format!("Cannot walk {} steps", steps));
+        }
+        let part2 = if options.reference {
+            garden.steps_walked(steps)?
+        } else {
+            garden.steps_quadratic(steps)?
+        }
+        .to_string();
 
         Ok(DayResult {
             part1,
@@ -134,3 +233,46 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadratic_matches_walk() {
+        let lines: Vec<String> = [
+            ".........",
+            ".##..#.#.",
+            ".#...#...",
+            "...#.....",
+            "....S....",
+            ".#.....#.",
+            "..##...#.",
+            ".#.....#.",
+            ".........",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let garden = Garden::from_lines(&lines);
+
+        for reference in [false, true] {
+            let options = RunOptions {
+                reference,
+                params: vec![("steps".to_owned(), "-1".to_owned())],
+                ..RunOptions::default()
+            };
+            assert!(Instance.run_input(&lines.clone().into(), &options).is_err());
+        }
+
+        for steps in [4, 13, 40, 101] {
+            assert_eq!(
+                garden.steps_quadratic(steps).unwrap(),
+                garden.steps_walked(steps).unwrap(),
+                "{} steps",
+                steps
+            );
+        }
+        assert!(garden.steps_walked(TARGET).is_err());
+    }
+}
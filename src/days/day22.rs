@@ -1,11 +1,18 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{cmp::Reverse, collections::HashMap, str::FromStr};
 
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use regex::Regex;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        export::{Drawing, Rgb},
+        parse::LinePattern,
+        profile,
+    },
+};
 
 pub struct Instance;
 
@@ -23,22 +30,15 @@ struct Block {
 }
 
 lazy_static! {
-    static ref BLOCK_REGEX: Regex = Regex::new(r"^(.*),(.*),(.*)~(.*),(.*),(.*)$").unwrap();
+    static ref BLOCK_PATTERN: LinePattern = LinePattern::new("{},{},{}~{},{},{}").unwrap();
 }
 
 impl FromStr for Block {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cs: Vec<_> = BLOCK_REGEX
-            .captures(s)
-            .ok_or("Bad format")?
-            .extract::<6>()
-            .1
-            .iter()
-            .map(|l| l.parse::<u16>())
-            .try_collect()
-            .map_err(|e| format!("{}", e))?;
+        let captures = BLOCK_PATTERN.captures(s)?;
+        let cs: Vec<u16> = (0..6).map(|i| captures.get(i)).try_collect()?;
 
         Ok(Block {
             start: Coord {
@@ -57,6 +57,7 @@ impl FromStr for Block {
 
 impl Block {
     fn coords(&self) -> Vec<Coord> {
+        let _span = profile::span("Block::coords");
         let mut ret = Vec::new();
         for x in self.start.x..=self.end.x {
             for y in self.start.y..=self.end.y {
@@ -95,6 +96,7 @@ struct Posititions(HashMap<(u16, u16), Vec<bool>>);
 
 impl Posititions {
     fn new(blocks: &[Block]) -> Self {
+        let _span = profile::span("Posititions::new");
         let max_z = blocks.iter().map(|c| c.end.z).max().unwrap() + 1;
         let mut positions = HashMap::new();
 
@@ -116,6 +118,7 @@ impl Posititions {
     }
 
     fn replace(&mut self, old_coords: Vec<Coord>, new_coords: Vec<Coord>) {
+        let _span = profile::span("Posititions::replace");
         self.remove(old_coords);
         for coord in new_coords {
             self.0.get_mut(&(coord.x, coord.y)).unwrap()[coord.z as usize] = true;
@@ -140,6 +143,7 @@ impl Blocks {
     }
 
     fn try_drop_one(&mut self) -> bool {
+        let _span = profile::span("Blocks::try_drop_one");
         let mut moved = 0;
 
         for b in self.0.iter_mut() {
@@ -163,7 +167,8 @@ impl Blocks {
         moved > 0
     }
 
-    fn to_bottom(&mut self) -> bool {
+    fn settle(&mut self) -> bool {
+        let _span = profile::span("Blocks::settle");
         let mut moved = false;
         while self.try_drop_one() {
             moved = true;
@@ -172,16 +177,22 @@ impl Blocks {
     }
 
     fn try_disintegrate(&mut self) -> (usize, usize) {
-        self.to_bottom();
+        let _span = profile::span("Blocks::try_disintegrate");
+        self.settle();
 
+        let parent = profile::parent();
         self.0
             .clone()
             .into_par_iter()
             .map(|b| {
-                let mut copy = self.clone();
+                let _span = profile::span_in(&parent, "disintegrate block");
+                let mut copy = {
+                    let _span = profile::span("Blocks::clone");
+                    self.clone()
+                };
                 copy.0.retain(|b2| &b != b2);
                 copy.1.remove(b.coords());
-                if !copy.to_bottom() {
+                if !copy.settle() {
                     (1, 0)
                 } else {
                     let mut old_blocks = self.0.clone();
@@ -199,14 +210,65 @@ impl Blocks {
             })
             .reduce(|| (0, 0), |(a, b), (c, d)| (a + c, b + d))
     }
+
+    // Side views of the stack, x-z on the left and y-z on the right
+    fn drawing(&self) -> Drawing {
+        let max = |f: fn(&Block) -> u16| self.0.iter().map(f).max().unwrap_or(0) as f64;
+        let (max_x, max_y, max_z) = (max(|b| b.end.x), max(|b| b.end.y), max(|b| b.end.z));
+        let gap = 2.0;
+
+        let mut drawing = Drawing::new(
+            (-1.0, -1.0),
+            (max_x + max_y + 2.0 + gap + 1.0, max_z + 1.0),
+            Rgb::DARK,
+        );
+        drawing.rect(-1.0, max_z, max_x + max_y + gap + 4.0, 1.0, Rgb::GRAY);
+
+        let colored = self.0.iter().enumerate().collect_vec();
+        let project = |b: &Block, along_x: bool| {
+            if along_x {
+                (b.start.x, b.end.x, b.start.y)
+            } else {
+                (b.start.y, b.end.y, b.start.x)
+            }
+        };
+        for (offset, along_x) in [(0.0, true), (max_x + 1.0 + gap, false)] {
+            // Paint far bricks first so nearer ones cover them
+            let by_depth = colored
+                .iter()
+                .sorted_by_key(|(_, b)| Reverse(project(b, along_x).2));
+            for (i, b) in by_depth {
+                let (from, to, _) = project(b, along_x);
+                drawing.rect(
+                    offset + from as f64,
+                    max_z - b.end.z as f64,
+                    (to - from + 1) as f64,
+                    (b.end.z - b.start.z + 1) as f64,
+                    Rgb::palette(*i),
+                );
+            }
+        }
+        drawing
+    }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let blocks: Vec<_> = lines.iter().map(|l| l.parse::<Block>()).try_collect()?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
+        let blocks: Vec<_> = {
+            let _span = profile::span("parse");
+            lines.iter().map(|l| l.parse::<Block>()).try_collect()?
+        };
         let mut blocks = Blocks::new(blocks);
 
         let (part1, part2) = blocks.try_disintegrate();
+        if let Some(path) = &options.export {
+            blocks.drawing().save(path)?;
+        }
         Ok(DayResult {
             part1: part1.to_string(),
             part2: Some(part2.to_string()),
@@ -230,7 +292,7 @@ mod tests {
         assert!(block.coords().contains(&Coord { x: 1, y: 0, z: 10 }));
 
         let mut blocks = Blocks::new(vec![block]);
-        blocks.to_bottom();
+        blocks.settle();
 
         assert_eq!(
             blocks.0[0],
@@ -251,7 +313,7 @@ mod tests {
         assert!(block.coords().len() == 6);
 
         let mut blocks = Blocks::new(vec![block]);
-        blocks.to_bottom();
+        blocks.settle();
 
         assert_eq!(
             blocks.0[0],
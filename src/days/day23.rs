@@ -2,7 +2,14 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        export::{Drawing, Rgb},
+        graph::Graph,
+    },
+};
 
 pub struct Instance;
 
@@ -35,6 +42,9 @@ impl Square {
     }
 }
 
+// Junction index to the neighbouring junction indices and their distances
+type Distances = HashMap<usize, Vec<(usize, usize)>>;
+
 #[derive(Debug)]
 struct Maze {
     squares: HashMap<(usize, usize), Square>,
@@ -77,6 +87,10 @@ impl Maze {
     }
 
     fn path(&self) -> usize {
+        self.longest_hike().len()
+    }
+
+    fn longest_hike(&self) -> HashSet<(usize, usize)> {
         #[derive(Debug, PartialEq, Eq, Clone)]
         struct Candidate((usize, usize), HashSet<(usize, usize)>);
 
@@ -124,20 +138,47 @@ impl Maze {
             }
         }
 
-        completed_paths.iter().map(|p| p.1.len()).max().unwrap()
+        completed_paths
+            .into_iter()
+            .max_by_key(|p| p.1.len())
+            .unwrap()
+            .1
     }
 
-    fn path_no_slope(&self) -> usize {
+    fn drawing(&self, hike: &HashSet<(usize, usize)>) -> Drawing {
+        let x_len = self.squares.keys().map(|c| c.0).max().unwrap();
+
+        let mut drawing = Drawing::new(
+            (1.0, 1.0),
+            (x_len as f64 + 1.0, self.y_len as f64 + 1.0),
+            Rgb::WHITE,
+        );
+        for (&(x, y), square) in &self.squares {
+            let fill = if hike.contains(&(x, y)) || (x, y) == self.end {
+                Rgb::RED
+            } else {
+                match square {
+                    Square::Wall => Rgb::GREEN,
+                    Square::Floor => continue,
+                    Square::Slope(_) => Rgb::GRAY,
+                }
+            };
+            drawing.rect(x as f64, y as f64, 1.0, 1.0, fill);
+        }
+        drawing
+    }
+
+    fn junction_graph(&self) -> (Vec<(usize, usize)>, Distances) {
         let mut junctions: HashSet<_> = self
             .squares
             .keys()
             .filter(|c| !matches!(self.squares[c], Square::Wall))
             .filter(|c| {
                 [
-                    ((c.0 + 1, c.1)),
-                    ((c.0 - 1, c.1)),
-                    ((c.0, c.1 + 1)),
-                    ((c.0, c.1 - 1)),
+                    (c.0 + 1, c.1),
+                    (c.0 - 1, c.1),
+                    (c.0, c.1 + 1),
+                    (c.0, c.1 - 1),
                 ]
                 .iter()
                 .filter_map(|c| self.squares.get(c))
@@ -154,10 +195,10 @@ impl Maze {
 
         for j in &junctions {
             let c = [
-                ((j.0 + 1, j.1)),
-                ((j.0 - 1, j.1)),
-                ((j.0, j.1 + 1)),
-                ((j.0, j.1 - 1)),
+                (j.0 + 1, j.1),
+                (j.0 - 1, j.1),
+                (j.0, j.1 + 1),
+                (j.0, j.1 - 1),
             ];
             for c in c {
                 let mut steps = 1;
@@ -174,10 +215,10 @@ impl Maze {
                     }
                     steps += 1;
                     let n = [
-                        ((cur.0 + 1, cur.1)),
-                        ((cur.0 - 1, cur.1)),
-                        ((cur.0, cur.1 + 1)),
-                        ((cur.0, cur.1 - 1)),
+                        (cur.0 + 1, cur.1),
+                        (cur.0 - 1, cur.1),
+                        (cur.0, cur.1 + 1),
+                        (cur.0, cur.1 - 1),
                     ];
                     for n in n {
                         if n == prev {
@@ -211,45 +252,104 @@ impl Maze {
                 .or_insert(vec![(t_i, d)]);
         }
 
-        let start_idx = junction_vec.binary_search(&self.start).unwrap();
-        let start_candidates: usize = 1 << start_idx;
-        let end_idx = junction_vec.binary_search(&self.end).unwrap();
-
-        let mut candidates = vec![(start_idx, 0, start_candidates)];
-
-        let mut max_distance = 0;
-        while let Some((c, d, v)) = candidates.pop() {
-            let ps = &path_distances[&c];
+        (junction_vec, path_distances)
+    }
 
-            for ps in ps {
-                let z = 1 << ps.0;
-                if v & z != 0 {
-                    continue;
+    // The longest hike ignoring slopes, with the junctions it passes through
+    fn path_no_slope(&self) -> (usize, Vec<(usize, usize)>) {
+        fn walk(
+            paths: &Distances,
+            end: usize,
+            (current, distance, visited): (usize, usize, usize),
+            route: &mut Vec<usize>,
+            best: &mut (usize, Vec<usize>),
+        ) {
+            if current == end {
+                if distance > best.0 {
+                    *best = (distance, route.clone());
                 }
-                if ps.0 == end_idx {
-                    if d + ps.1 > max_distance {
-                        max_distance = d + ps.1;
-                    }
+                return;
+            }
+            for &(next, steps) in paths.get(&current).into_iter().flatten() {
+                let z = 1 << next;
+                if visited & z != 0 {
                     continue;
                 }
-                let visited = v | z;
-
-                let total = d + ps.1;
-                candidates.push((ps.0, total, visited));
+                route.push(next);
+                walk(
+                    paths,
+                    end,
+                    (next, distance + steps, visited | z),
+                    route,
+                    best,
+                );
+                route.pop();
             }
         }
 
-        max_distance
+        let (junction_vec, path_distances) = self.junction_graph();
+
+        let start_idx = junction_vec.binary_search(&self.start).unwrap();
+        let end_idx = junction_vec.binary_search(&self.end).unwrap();
+
+        let mut best = (0, Vec::new());
+        walk(
+            &path_distances,
+            end_idx,
+            (start_idx, 0, 1 << start_idx),
+            &mut vec![start_idx],
+            &mut best,
+        );
+
+        let (distance, route) = best;
+        (
+            distance,
+            route.into_iter().map(|i| junction_vec[i]).collect(),
+        )
+    }
+
+    fn graph(&self, route: &[(usize, usize)]) -> Graph {
+        let (junction_vec, path_distances) = self.junction_graph();
+        let name = |(x, y): (usize, usize)| format!("{},{}", x, y);
+
+        let mut graph = Graph::new(false);
+        for (&from, paths) in &path_distances {
+            for &(to, distance) in paths.iter().filter(|(to, _)| from < *to) {
+                graph
+                    .edge(&name(junction_vec[from]), &name(junction_vec[to]))
+                    .weight(distance as i64);
+            }
+        }
+        graph.node(&name(self.start)).kind("start");
+        graph.node(&name(self.end)).kind("end");
+        graph.highlight_path(&route.iter().copied().map(name).collect_vec());
+        graph
     }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let maze = Maze::from_lines(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let part1 = maze.path().to_string();
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let maze = Maze::from_lines(&input.to_lines());
 
-        let part2 = maze.path_no_slope().to_string();
+        let part1 = match &options.export {
+            Some(path) => {
+                let hike = maze.longest_hike();
+                maze.drawing(&hike).save(path)?;
+                hike.len()
+            }
+            None => maze.path(),
+        }
+        .to_string();
+
+        let (part2, route) = maze.path_no_slope();
+        if let Some(path) = &options.graph {
+            maze.graph(&route).save(path)?;
+        }
+        let part2 = part2.to_string();
 
         Ok(DayResult {
             part1,
@@ -257,3 +357,20 @@ impl Day for Instance {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_route() {
+        let input = Input::read("fixtures/day23.txt").unwrap();
+        let maze = Maze::from_lines(&input.to_lines());
+
+        let (distance, route) = maze.path_no_slope();
+        assert_eq!(distance, 154);
+        assert_eq!(route.first(), Some(&maze.start));
+        assert_eq!(route.last(), Some(&maze.end));
+        assert!(route.iter().all_unique());
+    }
+}
@@ -1,8 +1,11 @@
-use std::{mem::swap, str::FromStr};
+use std::{mem::swap, ops::RangeInclusive, str::FromStr};
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult},
+    util::ratio::Ratio,
+};
 
 pub struct Instance;
 
@@ -45,37 +48,81 @@ impl FromStr for Hailstone {
     }
 }
 
-impl Hailstone {
-    fn cross_xy(&self, other: &Hailstone) -> Option<(i128, i128)> {
-        let x1 = self.position.x;
-        let x2 = self.position.x + self.velocity.x;
-        let x3 = other.position.x;
-        let x4 = other.position.x + other.velocity.x;
-
-        let y1 = self.position.y;
-        let y2 = self.position.y + self.velocity.y;
-        let y3 = other.position.y;
-        let y4 = other.position.y + other.velocity.y;
-
-        let denom_x = (x1 - x2) * (y3 - y4) - (y1 - y2) * (x3 - x4);
-        let denom_y = (x1 - x2) * (y3 - y4) - (y1 - y2) * (x3 - x4);
+fn overflow(hailstone: &Hailstone) -> String {
+    format!("Overflow following hailstone {:?}", hailstone)
+}
 
-        if denom_x != 0 && denom_y != 0 {
-            let px = ((x1 * y2 - y1 * x2) * (x3 - x4) - (x1 - x2) * (x3 * y4 - y3 * x4)) / denom_x;
-            let py = ((x1 * y2 - y1 * x2) * (y3 - y4) - (y1 - y2) * (x3 * y4 - y3 * x4)) / denom_y;
+/// `a * d - b * c`, or `None` on overflow
+fn det(a: i128, b: i128, c: i128, d: i128) -> Option<i128> {
+    a.checked_mul(d)?.checked_sub(b.checked_mul(c)?)
+}
 
-            let t1 = self.velocity.x == 0 || ((px - x1) / (self.velocity.x)) > 0;
-            let t2 = other.velocity.x == 0 || ((px - x3) / (other.velocity.x)) > 0;
+impl Hailstone {
+    fn time_at(&self, px: Ratio, py: Ratio) -> Option<Ratio> {
+        if self.velocity.x != 0 {
+            px.checked_sub(Ratio::from(self.position.x))?
+                .checked_div(Ratio::from(self.velocity.x))
+        } else {
+            py.checked_sub(Ratio::from(self.position.y))?
+                .checked_div(Ratio::from(self.velocity.y))
+        }
+    }
 
-            if t1 && t2 {
-                Some((px, py))
+    fn cross_xy(&self, other: &Hailstone) -> Result<Option<(Ratio, Ratio)>, String> {
+        let numerators = || {
+            let x1 = self.position.x;
+            let x2 = self.position.x.checked_add(self.velocity.x)?;
+            let x3 = other.position.x;
+            let x4 = other.position.x.checked_add(other.velocity.x)?;
+
+            let y1 = self.position.y;
+            let y2 = self.position.y.checked_add(self.velocity.y)?;
+            let y3 = other.position.y;
+            let y4 = other.position.y.checked_add(other.velocity.y)?;
+
+            let dx12 = x1.checked_sub(x2)?;
+            let dx34 = x3.checked_sub(x4)?;
+            let dy12 = y1.checked_sub(y2)?;
+            let dy34 = y3.checked_sub(y4)?;
+
+            let denom = det(dx12, dy12, dx34, dy34)?;
+            let a = det(x1, y1, x2, y2)?;
+            let b = det(x3, y3, x4, y4)?;
+            Some((det(a, dx12, b, dx34)?, det(a, dy12, b, dy34)?, denom))
+        };
+        let (px, py, denom) = numerators().ok_or_else(|| overflow(self))?;
+
+        if denom != 0 {
+            let px = Ratio::new(px, denom).ok_or_else(|| overflow(self))?;
+            let py = Ratio::new(py, denom).ok_or_else(|| overflow(self))?;
+
+            let zero = Ratio::from(0);
+            let t1 = self.time_at(px, py).ok_or_else(|| overflow(self))?;
+            let t2 = other.time_at(px, py).ok_or_else(|| overflow(other))?;
+            if t1 > zero && t2 > zero {
+                Ok(Some((px, py)))
             } else {
-                None
+                Ok(None)
             }
         } else {
-            None
+            Ok(None)
+        }
+    }
+}
+
+fn crossings_within(
+    hailstones: &[Hailstone],
+    area: RangeInclusive<Ratio>,
+) -> Result<usize, String> {
+    let mut crossed = 0;
+    for (a, b) in hailstones.iter().tuple_combinations() {
+        if let Some((x, y)) = a.cross_xy(b)? {
+            if area.contains(&x) && area.contains(&y) {
+                crossed += 1;
+            }
         }
     }
+    Ok(crossed)
 }
 
 const SEARCH_SPACE: i128 = 500;
@@ -84,30 +131,19 @@ impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
         let mut hailstones: Vec<Hailstone> = lines.iter().map(|l| l.parse()).try_collect()?;
 
-        let pairs = hailstones.iter().tuple_combinations();
-
-        let mut crossed = 0;
-        for (a, b) in pairs {
-            if let Some((x, y)) = a.cross_xy(b) {
-                if (200000000000000..400000000000000).contains(&x)
-                    && (200000000000000..400000000000000).contains(&y)
-                {
-                    crossed += 1;
-                }
-            }
-        }
+        let area = Ratio::from(200000000000000)..=Ratio::from(400000000000000);
+        let crossed = crossings_within(&hailstones, area)?;
 
-        fn same_source(hailstones: &Vec<Hailstone>) -> Option<(i128, i128)> {
-            let mut combinations = hailstones
-                .iter()
-                .tuple_combinations()
-                .filter_map(|(a, b)| a.cross_xy(b));
-            if let Some(v) = combinations.next() {
-                if combinations.all(|c| c == v) {
-                    return Some(v);
+        fn same_source(hailstones: &[Hailstone]) -> Result<Option<(Ratio, Ratio)>, String> {
+            let mut source = None;
+            for (a, b) in hailstones.iter().tuple_combinations() {
+                match (a.cross_xy(b)?, source) {
+                    (Some(c), None) => source = Some(c),
+                    (Some(c), Some(v)) if c != v => return Ok(None),
+                    _ => {}
                 }
             }
-            None
+            Ok(source)
         }
 
         let part2;
@@ -124,7 +160,7 @@ impl Day for Instance {
                     h.velocity.y += 1;
                 });
 
-                if let Some(v1) = same_source(&hailstones) {
+                if let Some(v1) = same_source(&hailstones)? {
                     hailstones.iter_mut().for_each(|h| {
                         h.velocity.z -= SEARCH_SPACE;
                         swap(&mut h.velocity.y, &mut h.velocity.z);
@@ -134,8 +170,11 @@ impl Day for Instance {
                         hailstones.iter_mut().for_each(|h| {
                             h.velocity.y += 1;
                         });
-                        if let Some(v2) = same_source(&hailstones) {
-                            part2 = Some(v1.0 + v1.1 + v2.1);
+                        if let Some(v2) = same_source(&hailstones)? {
+                            part2 =
+                                v1.0.checked_add(v1.1)
+                                    .and_then(|p| p.checked_add(v2.1))
+                                    .ok_or("Overflow adding rock position")?;
                             break 'outer;
                         }
                     }
@@ -146,9 +185,50 @@ impl Day for Instance {
             });
         }
 
+        let part2 = part2
+            .to_integer()
+            .ok_or(format!("Rock position is not integral: {}", part2))?;
+
         Ok(DayResult {
             part1: crossed.to_string(),
-            part2: part2.map(|i| i.to_string()),
+            part2: Some(part2.to_string()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_fractional_crossings_at_the_boundary() {
+        let hailstones: Vec<Hailstone> = [
+            "399999999999999, 399999999999999, 0 @ 1, 1, 0",
+            "400000000000001, 399999999999998, 0 @ -1, 1, 0",
+            "400000000000000, 400000000000000, 0 @ 1, 1, 0",
+            "400000000000002, 399999999999999, 0 @ -1, 1, 0",
+        ]
+        .iter()
+        .map(|l| l.parse())
+        .try_collect()
+        .unwrap();
+
+        // Only the first two cross inside, half a unit below the edge, the first and last
+        // cross half a unit beyond it
+        let area = Ratio::from(200000000000000)..=Ratio::from(400000000000000);
+        assert_eq!(crossings_within(&hailstones, area).unwrap(), 1);
+
+        let (x, y) = hailstones[0].cross_xy(&hailstones[1]).unwrap().unwrap();
+        assert_eq!(x, Ratio::new(799999999999999, 2).unwrap());
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn reports_overflowing_crossings() {
+        let a: Hailstone = format!("{0}, {0}, 0 @ 1, 2, 0", i128::MAX / 2)
+            .parse()
+            .unwrap();
+        let b: Hailstone = "0, 1, 0 @ 1, -1, 0".parse().unwrap();
+        assert!(a.cross_xy(&b).is_err());
+    }
+}
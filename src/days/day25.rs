@@ -1,6 +1,10 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{graph::Graph, parse::key_values},
+};
 
 pub struct Instance;
 
@@ -9,11 +13,10 @@ struct Wires<'a> {
 }
 
 impl<'a> Wires<'a> {
-    fn new(lines: &'a [String]) -> Self {
+    fn new(lines: &'a [String]) -> Result<Self, String> {
         let mut connected = HashMap::new();
 
-        for line in lines {
-            let (from, tos) = line.split_once(": ").unwrap();
+        for (from, tos) in key_values(lines, ": ")? {
             for to in tos.split(' ') {
                 connected
                     .entry(from)
@@ -25,7 +28,7 @@ impl<'a> Wires<'a> {
                     .or_insert(vec![from]);
             }
         }
-        Wires { connected }
+        Ok(Wires { connected })
     }
 }
 
@@ -95,14 +98,134 @@ impl Wires<'_> {
         }
         panic!("couldn't find split")
     }
+
+    fn min_cut_reference(&self) -> Option<usize> {
+        let side = self.min_cut_partition()?;
+        Some(side.len() * (self.connected.len() - side.len()))
+    }
+
+    fn graph(&self) -> Graph {
+        let side = self.min_cut_partition().unwrap_or_default();
+        let mut graph = Graph::new(false);
+        for (&from, tos) in &self.connected {
+            let kind = if side.contains(from) { "a" } else { "b" };
+            graph.node(from).kind(kind);
+            for &to in tos.iter().filter(|&&to| from < to) {
+                let edge = graph.edge(from, to);
+                if side.contains(from) != side.contains(to) {
+                    edge.highlight();
+                }
+            }
+        }
+        graph
+    }
+
+    // The side of a three wire cut containing an arbitrary node
+    fn min_cut_partition(&self) -> Option<HashSet<&str>> {
+        let nodes: Vec<_> = self.connected.keys().copied().collect();
+        let index: HashMap<_, _> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let edges: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|n| self.connected[n].iter().map(|c| index[c]).collect())
+            .collect();
+
+        let source = 0;
+        for sink in 1..nodes.len() {
+            let mut flow: HashMap<(usize, usize), i32> = HashMap::new();
+            let mut total = 0;
+
+            loop {
+                let mut previous = HashMap::new();
+                previous.insert(source, source);
+                let mut queue = VecDeque::from([source]);
+                while let Some(v) = queue.pop_front() {
+                    for &n in &edges[v] {
+                        let residual = 1 - flow.get(&(v, n)).unwrap_or(&0);
+                        if residual > 0 && !previous.contains_key(&n) {
+                            previous.insert(n, v);
+                            queue.push_back(n);
+                        }
+                    }
+                }
+
+                if !previous.contains_key(&sink) {
+                    if total == 3 {
+                        return Some(previous.keys().map(|&i| nodes[i]).collect());
+                    }
+                    break;
+                }
+
+                total += 1;
+                if total > 3 {
+                    break;
+                }
+
+                let mut v = sink;
+                while v != source {
+                    let u = previous[&v];
+                    *flow.entry((u, v)).or_default() += 1;
+                    *flow.entry((v, u)).or_default() -= 1;
+                    v = u;
+                }
+            }
+        }
+
+        None
+    }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let wires = Wires::new(&lines);
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
+        let wires = Wires::new(&lines)?;
+        if let Some(path) = &options.graph {
+            wires.graph().save(path)?;
+        }
 
-        let part1 = wires.traverse().to_string();
+        let part1 = if options.reference {
+            wires
+                .min_cut_reference()
+                .ok_or("No cut of three wires splits the graph")?
+        } else {
+            wires.traverse()
+        }
+        .to_string();
 
         Ok(DayResult { part1, part2: None })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_matches_fast_path() {
+        let lines: Vec<String> = [
+            "jqt: rhn xhk nvd",
+            "rsh: frs pzl lsr",
+            "xhk: hfx",
+            "cmg: qnr nvd lhk bvb",
+            "rhn: xhk bvb hfx",
+            "bvb: xhk hfx",
+            "pzl: lsr hfx nvd",
+            "qnr: nvd",
+            "ntq: jqt hfx bvb xhk",
+            "nvd: lhk",
+            "lsr: lhk",
+            "rzs: qnr cmg lsr rsh",
+            "frs: qnr lhk lsr",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let wires = Wires::new(&lines).unwrap();
+
+        assert_eq!(wires.traverse(), 54);
+        assert_eq!(wires.min_cut_reference(), Some(54));
+    }
+}
@@ -1,22 +1,91 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+};
 
 pub struct Instance;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Adjacency {
+    Diagonal,
+    Orthogonal,
+}
+
+impl FromStr for Adjacency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "diagonal" => Ok(Adjacency::Diagonal),
+            "orthogonal" => Ok(Adjacency::Orthogonal),
+            _ => Err(format!("Unknown adjacency: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Count {
+    fn matches(&self, n: usize) -> bool {
+        match *self {
+            Count::Exactly(c) => n == c,
+            Count::AtLeast(c) => n >= c,
+        }
+    }
+}
+
+// Written as `*:2` for exactly two parts or `*:2+` for at least two
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GearRule {
+    symbol: char,
+    count: Count,
+}
+
+impl FromStr for GearRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("Bad gear rule: {:?}", s);
+        let (symbol, count) = s.split_once(':').ok_or_else(bad)?;
+        let mut symbol = symbol.chars();
+        let (Some(symbol), None) = (symbol.next(), symbol.next()) else {
+            return Err(bad());
+        };
+        let count = match count.strip_suffix('+') {
+            Some(n) => Count::AtLeast(n.parse().map_err(|_| bad())?),
+            None => Count::Exactly(count.parse().map_err(|_| bad())?),
+        };
+        Ok(GearRule { symbol, count })
+    }
+}
+
+#[derive(Debug)]
 struct Part {
-    x: u8,
-    y: u8,
-    value: u16,
-    len: u8,
+    x: usize,
+    y: usize,
+    value: u32,
+    len: usize,
 }
 
 impl Part {
-    fn neighbours(&self) -> Vec<(u8, u8)> {
+    fn neighbours(&self, adjacency: Adjacency) -> Vec<(usize, usize)> {
         let mut neighbours = Vec::new();
         let y = self.y;
 
-        for x in (self.x.saturating_sub(1))..=(self.x + self.len) {
+        let (from, to) = match adjacency {
+            Adjacency::Diagonal => (self.x.saturating_sub(1), self.x + self.len),
+            Adjacency::Orthogonal => (self.x, self.x + self.len - 1),
+        };
+        for x in from..=to {
             if y > 0 {
                 neighbours.push((x, y - 1));
             }
@@ -33,27 +102,46 @@ impl Part {
     }
 }
 
+#[derive(Debug)]
+struct Gear {
+    symbol: char,
+    at: (usize, usize),
+    parts: Vec<u32>,
+}
+
+impl Gear {
+    fn ratio(&self) -> Result<u64, String> {
+        self.parts
+            .iter()
+            .try_fold(1u64, |ratio, &p| ratio.checked_mul(p as u64))
+            .ok_or(format!(
+                "Overflow multiplying {} at {},{}: {:?}",
+                self.symbol, self.at.0, self.at.1, self.parts
+            ))
+    }
+}
+
 struct Schematic {
     parts: Vec<Part>,
-    symbols: HashMap<(u8, u8), char>,
+    symbols: HashMap<(usize, usize), char>,
 }
 
 fn push_part(
     parts: &mut Vec<Part>,
     n_acc: &mut Option<Vec<char>>,
-    n_pos: &mut Option<(u8, u8)>,
+    n_pos: &mut Option<(usize, usize)>,
 ) -> Result<(), String> {
     if let (Some(acc), Some(p)) = (n_acc.as_ref(), n_pos.as_ref()) {
         let n = acc
             .iter()
             .collect::<String>()
-            .parse::<u16>()
+            .parse::<u32>()
             .map_err(|e| format!("{}: {}", e, acc.iter().collect::<String>()))?;
         parts.push(Part {
             x: p.0,
             y: p.1,
             value: n,
-            len: acc.len() as u8,
+            len: acc.len(),
         });
         *n_acc = None;
         *n_pos = None;
@@ -66,24 +154,24 @@ impl FromStr for Schematic {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut n_acc: Option<Vec<char>> = None;
-        let mut n_pos: Option<(u8, u8)> = None;
+        let mut n_pos: Option<(usize, usize)> = None;
 
         let mut parts = Vec::new();
         let mut symbols = HashMap::new();
 
-        for (y, l) in s.split('\n').enumerate() {
+        for (y, l) in s.lines().enumerate() {
             for (x, c) in l.chars().enumerate() {
                 if c.is_ascii_digit() {
                     if let Some(n) = n_acc.as_mut() {
                         n.push(c);
                     } else {
                         n_acc = Some(vec![c]);
-                        n_pos = Some((x as u8, y as u8));
+                        n_pos = Some((x, y));
                     }
                 } else {
                     push_part(&mut parts, &mut n_acc, &mut n_pos)?;
                     if c != '.' {
-                        symbols.insert((x as u8, y as u8), c);
+                        symbols.insert((x, y), c);
                     }
                 }
             }
@@ -95,55 +183,252 @@ impl FromStr for Schematic {
 }
 
 impl Schematic {
-    fn part_numbers(&self) -> Vec<u16> {
-        let mut parts = Vec::new();
-
-        for part in self.parts.iter() {
-            for neighbour in part.neighbours() {
-                if self.symbols.contains_key(&neighbour) {
-                    parts.push(part.value);
-                    break;
-                }
-            }
-        }
+    fn parts_with_symbols(&self, adjacency: Adjacency) -> Vec<(&Part, Vec<char>)> {
+        self.parts
+            .iter()
+            .map(|part| {
+                let symbols = part
+                    .neighbours(adjacency)
+                    .iter()
+                    .filter_map(|n| self.symbols.get(n).copied())
+                    .collect();
+                (part, symbols)
+            })
+            .collect()
+    }
 
-        parts
+    fn part_numbers(&self, adjacency: Adjacency) -> Vec<u32> {
+        self.parts_with_symbols(adjacency)
+            .into_iter()
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(part, _)| part.value)
+            .collect()
     }
 
-    fn gears(&self) -> Vec<u32> {
-        let mut gears = HashMap::new();
+    fn gears(&self, rule: GearRule, adjacency: Adjacency) -> Vec<Gear> {
+        let mut gears = BTreeMap::new();
 
         for part in self.parts.iter() {
-            for neighbour in part.neighbours() {
-                if let Some(s) = self.symbols.get(&neighbour) {
-                    if *s == '*' {
-                        gears
-                            .entry(neighbour)
-                            .and_modify(|p: &mut Vec<u16>| p.push(part.value))
-                            .or_insert(vec![part.value]);
-                    }
+            for neighbour in part.neighbours(adjacency) {
+                if self.symbols.get(&neighbour) == Some(&rule.symbol) {
+                    gears
+                        .entry((neighbour.1, neighbour.0))
+                        .or_insert_with(Vec::new)
+                        .push(part.value);
                 }
             }
         }
 
         gears
-            .values()
-            .filter(|l| l.len() == 2)
-            .map(|l| l.iter().map(|i| *i as u32).product::<u32>())
+            .into_iter()
+            .filter(|(_, parts)| rule.count.matches(parts.len()))
+            .map(|((y, x), parts)| Gear {
+                symbol: rule.symbol,
+                at: (x, y),
+                parts,
+            })
             .collect()
     }
 }
 
+fn parse(input: &Input, options: &RunOptions) -> Result<(Schematic, Adjacency, GearRule), String> {
+    let schematic = input.as_str().parse::<Schematic>()?;
+    let adjacency: Adjacency = options.param("adjacency").unwrap_or("diagonal").parse()?;
+    let rule: GearRule = options.param("gear").unwrap_or("*:2").parse()?;
+    Ok((schematic, adjacency, rule))
+}
+
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let schematic = lines.join("\n").parse::<Schematic>()?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let (schematic, adjacency, rule) = parse(input, options)?;
 
-        let part1: u32 = schematic.part_numbers().iter().map(|i| *i as u32).sum();
-        let part2: u32 = schematic.gears().iter().sum();
+        let part1: u64 = schematic
+            .part_numbers(adjacency)
+            .iter()
+            .map(|&i| i as u64)
+            .sum();
+        let part2 = schematic
+            .gears(rule, adjacency)
+            .iter()
+            .try_fold(0u64, |sum, gear| {
+                sum.checked_add(gear.ratio()?)
+                    .ok_or("Overflow summing gear ratios".to_owned())
+            })?;
 
         Ok(DayResult {
             part1: part1.to_string(),
             part2: Some(part2.to_string()),
         })
     }
+
+    // `list=parts` gives every part with its neighbouring symbols, `list=gears` every gear
+    fn queries(
+        &self,
+        input: &Input,
+        options: &RunOptions,
+    ) -> Result<Vec<(String, String)>, String> {
+        let Some(list) = options.param("list") else {
+            return Ok(Vec::new());
+        };
+
+        let (schematic, adjacency, rule) = parse(input, options)?;
+        match list {
+            "parts" => Ok(schematic
+                .parts_with_symbols(adjacency)
+                .into_iter()
+                .map(|(part, symbols)| {
+                    (
+                        format!("{} at {},{}", part.value, part.x, part.y),
+                        format!("{:?}", symbols),
+                    )
+                })
+                .collect()),
+            "gears" => Ok(schematic
+                .gears(rule, adjacency)
+                .into_iter()
+                .map(|gear| {
+                    (
+                        format!("{} at {},{}", gear.symbol, gear.at.0, gear.at.1),
+                        format!("{:?}", gear.parts),
+                    )
+                })
+                .collect()),
+            _ => Err(format!("Unknown list: {}", list)),
+        }
+    }
+
+    fn version(&self) -> u32 {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(params: &[(&str, &str)]) -> RunOptions {
+        RunOptions {
+            params: params
+                .iter()
+                .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+                .collect(),
+            ..RunOptions::default()
+        }
+    }
+
+    fn run(lines: &[&str], params: &[(&str, &str)]) -> Result<DayResult, String> {
+        let input: Input = lines
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+            .into();
+        Instance.run_input(&input, &options(params))
+    }
+
+    #[test]
+    fn example() {
+        let input = Input::read("fixtures/day3.txt").unwrap();
+        let result = Instance.run_input(&input, &options(&[])).unwrap();
+
+        assert_eq!(result.part1, "4361");
+        assert_eq!(result.part2.as_deref(), Some("467835"));
+    }
+
+    #[test]
+    fn parses_gear_rules() {
+        assert_eq!(
+            "*:2".parse(),
+            Ok(GearRule {
+                symbol: '*',
+                count: Count::Exactly(2)
+            })
+        );
+        assert_eq!(
+            "#:1+".parse(),
+            Ok(GearRule {
+                symbol: '#',
+                count: Count::AtLeast(1)
+            })
+        );
+        assert!("**:2".parse::<GearRule>().is_err());
+        assert!("*:".parse::<GearRule>().is_err());
+        assert!("*2".parse::<GearRule>().is_err());
+    }
+
+    #[test]
+    fn counts_exactly_or_at_least() {
+        let lines = ["1.2.3", ".#.#.", "4...5"];
+
+        // The left # touches 1, 2 and 4, the right one 2, 3 and 5
+        let exactly = |n: &str| run(&lines, &[("gear", &format!("#:{}", n))]).unwrap();
+        assert_eq!(exactly("3").part2.as_deref(), Some("38"));
+        assert_eq!(exactly("2").part2.as_deref(), Some("0"));
+
+        let result = run(&lines, &[("gear", "#:1+")]).unwrap();
+        assert_eq!(result.part2.as_deref(), Some("38"));
+        let result = run(&lines, &[("gear", "#:4+")]).unwrap();
+        assert_eq!(result.part2.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn diagonal_or_orthogonal() {
+        let lines = ["1..", ".*.", "..2", "3*4"];
+
+        // Diagonally 2 touches both gears, leaving the lower one with three parts
+        let result = run(&lines, &[]).unwrap();
+        assert_eq!(result.part1, "10");
+        assert_eq!(result.part2.as_deref(), Some("2"));
+
+        let result = run(&lines, &[("adjacency", "orthogonal")]).unwrap();
+        assert_eq!(result.part1, "7");
+        assert_eq!(result.part2.as_deref(), Some("12"));
+
+        assert!(run(&lines, &[("adjacency", "hex")]).is_err());
+    }
+
+    #[test]
+    fn parses_wide_schematics() {
+        let line = format!("{}12*.", ".".repeat(300));
+        let result = run(&[&line, &format!("{}3", ".".repeat(302))], &[]).unwrap();
+        assert_eq!(result.part1, "15");
+        assert_eq!(result.part2.as_deref(), Some("36"));
+    }
+
+    #[test]
+    fn reports_overflowing_ratios() {
+        let lines = ["9999999*9999999", "9999999........"];
+        assert!(run(&lines, &[("gear", "*:3")]).is_err());
+    }
+
+    #[test]
+    fn lists_parts_and_gears() {
+        let input = Input::read("fixtures/day3.txt").unwrap();
+
+        let parts = Instance
+            .queries(&input, &options(&[("list", "parts")]))
+            .unwrap();
+        assert_eq!(parts.len(), 10);
+        assert_eq!(parts[0], ("467 at 0,0".to_owned(), "['*']".to_owned()));
+        assert_eq!(parts[1], ("114 at 5,0".to_owned(), "[]".to_owned()));
+
+        let gears = Instance
+            .queries(&input, &options(&[("list", "gears")]))
+            .unwrap();
+        assert_eq!(
+            gears,
+            vec![
+                ("* at 3,1".to_owned(), "[467, 35]".to_owned()),
+                ("* at 5,8".to_owned(), "[755, 598]".to_owned()),
+            ]
+        );
+
+        assert!(Instance.queries(&input, &options(&[])).unwrap().is_empty());
+        assert!(Instance
+            .queries(&input, &options(&[("list", "cogs")]))
+            .is_err());
+    }
 }
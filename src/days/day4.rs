@@ -1,54 +1,57 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
+use std::{collections::HashSet, str::FromStr};
+
+use lazy_static::lazy_static;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::parse::{integers, LinePattern, ParseError},
+};
 
 pub struct Instance;
 
-struct Card {
-    id: u8,
-    winners: Vec<u8>,
-    numbers: Vec<u8>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyRule {
+    Cap,
+    // Copies won past the last card come from the front, they are kept but not scratched again
+    Wrap,
+    Error,
 }
 
-impl FromStr for Card {
+impl FromStr for CopyRule {
     type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(": ");
-        let card_id = split.next().unwrap();
-        let winners_numbers = split.next().ok_or("missing numbers")?;
-
-        let id = card_id[5..card_id.len()]
-            .replace(' ', "")
-            .parse::<u8>()
-            .map_err(|e| e.to_string())?;
 
-        let mut i = 0;
-        let mut is_winners = true;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cap" => Ok(CopyRule::Cap),
+            "wrap" => Ok(CopyRule::Wrap),
+            "error" => Ok(CopyRule::Error),
+            _ => Err(format!("Unknown copy rule: {}", s)),
+        }
+    }
+}
 
-        let mut winners = Vec::new();
-        let mut numbers = Vec::new();
+struct Card {
+    id: u32,
+    winners: Vec<u32>,
+    numbers: Vec<u32>,
+}
 
-        while i < winners_numbers.len() {
-            if &winners_numbers[i..=i] == "|" {
-                is_winners = false;
-                i += 2;
-            }
-            let n = winners_numbers[i..i + 2]
-                .replace(' ', "")
-                .parse::<u8>()
-                .map_err(|e| format!("{}: {}", e, &winners_numbers[i..i + 2]))?;
+lazy_static! {
+    static ref CARD_PATTERN: LinePattern = LinePattern::new("{}:{}|{}").unwrap();
+}
 
-            if is_winners {
-                winners.push(n);
-            } else {
-                numbers.push(n);
-            }
+impl FromStr for Card {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let card = CARD_PATTERN.captures(s)?;
 
-            i += 3;
-        }
+        let id = match integers::<u32>(card.str(0))?[..] {
+            [.., id] => id,
+            [] => card.get::<u32>(0)?,
+        };
+        let winners = integers(card.str(1))?;
+        let numbers = integers(card.str(2))?;
 
         Ok(Card {
             id,
@@ -59,47 +62,153 @@ impl FromStr for Card {
 }
 
 impl Card {
-    fn wins(&self) -> u8 {
-        let winners: HashSet<&u8> = HashSet::from_iter(self.winners.iter());
-        let numbers: HashSet<&u8> = HashSet::from_iter(self.numbers.iter());
-        winners.intersection(&numbers).count() as u8
+    fn wins(&self) -> usize {
+        let winners: HashSet<&u32> = HashSet::from_iter(self.winners.iter());
+        let numbers: HashSet<&u32> = HashSet::from_iter(self.numbers.iter());
+        winners.intersection(&numbers).count()
+    }
+
+    fn score(&self) -> Result<u64, String> {
+        match self.wins() {
+            0 => Ok(0),
+            wins => 1u64.checked_shl(wins as u32 - 1).ok_or(format!(
+                "Card {} scores 2^{} points",
+                self.id,
+                wins - 1
+            )),
+        }
     }
+}
+
+// Number of copies held of each card, in input order
+fn copies(cards: &[Card], rule: CopyRule) -> Result<Vec<u64>, String> {
+    let mut counts = vec![1u64; cards.len()];
 
-    fn score(&self) -> u16 {
-        if self.wins() == 0 {
-            0
-        } else {
-            1 << (self.wins() - 1)
+    for (i, card) in cards.iter().enumerate() {
+        // Wrapping can land back on this card, which must not win from its own new copies
+        let held = counts[i];
+        for target in i + 1..=i + card.wins() {
+            let target = if target < cards.len() {
+                target
+            } else {
+                match rule {
+                    CopyRule::Cap => break,
+                    CopyRule::Wrap => target % cards.len(),
+                    CopyRule::Error => Err(format!(
+                        "Card {} wins {} copies but only {} cards follow it",
+                        card.id,
+                        card.wins(),
+                        cards.len() - i - 1
+                    ))?,
+                }
+            };
+            counts[target] = counts[target]
+                .checked_add(held)
+                .ok_or(format!("Card {} wins too many copies to count", card.id))?;
         }
     }
+
+    Ok(counts)
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let cards = lines
-            .iter()
-            .map(|l| l.parse::<Card>())
-            .collect::<Result<Vec<_>, _>>()?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let total_score = cards.iter().map(|c| c.score()).sum::<u16>();
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let rule: CopyRule = options.param("copies").unwrap_or("cap").parse()?;
 
-        let mut card_counts = HashMap::new();
+        let cards = input
+            .lines()
+            .zip(1..)
+            .map(|(l, n)| l.parse::<Card>().map_err(|e| e.on_line(n)))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        for card in cards.iter().rev() {
-            let mut total = 1;
-            for w in 1..=card.wins() {
-                let id = card.id + w;
-                total += card_counts[&id];
-            }
+        let total_score = cards.iter().try_fold(0u64, |total, c| {
+            total
+                .checked_add(c.score()?)
+                .ok_or("Total score overflows".to_owned())
+        })?;
 
-            card_counts.insert(card.id, total);
+        let copies = copies(&cards, rule)?;
+        if options.verbose {
+            for (card, copies) in cards.iter().zip(&copies) {
+                println!("Card {}: {} wins, {} copies", card.id, card.wins(), copies);
+            }
         }
 
-        let total_cards: u32 = card_counts.values().sum();
+        let total_cards = copies
+            .iter()
+            .try_fold(0u64, |total, &c| total.checked_add(c))
+            .ok_or("Total card count overflows")?;
 
         Ok(DayResult {
             part1: total_score.to_string(),
             part2: Some(total_cards.to_string()),
         })
     }
+
+    fn version(&self) -> u32 {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_copy_rules() {
+        let cards: Vec<Card> = ["Card 1: 1 2 | 1 2", "Card 2: 3 | 4", "3:5 6|6 5"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        assert_eq!(cards[2].id, 3);
+
+        assert_eq!(copies(&cards, CopyRule::Cap).unwrap(), vec![1, 2, 2]);
+        assert_eq!(copies(&cards, CopyRule::Wrap).unwrap(), vec![3, 4, 2]);
+        assert!(copies(&cards, CopyRule::Error).is_err());
+    }
+
+    #[test]
+    fn wraps_past_every_card() {
+        let cards: Vec<Card> = ["Card 1: 1 2 3 | 1 2 3", "Card 2: 3 | 4"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+
+        // Card 1 wins copies of 2, 1 and 2 again, each time for the single card it started with
+        assert_eq!(copies(&cards, CopyRule::Wrap).unwrap(), vec![2, 3]);
+    }
+
+    #[test]
+    fn copies_overflow() {
+        // Each card wins a copy of every later one, so card n ends up with 2^(n-1) copies
+        let cards: Vec<Card> = (0..66)
+            .map(|i| {
+                let numbers = (1..66 - i).map(|n| n.to_string()).collect::<Vec<_>>();
+                let numbers = numbers.join(" ");
+                format!("Card {}: {} | {}", i + 1, numbers, numbers)
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+
+        assert_eq!(copies(&cards[..64], CopyRule::Cap).unwrap()[63], 1 << 63);
+        assert!(copies(&cards, CopyRule::Cap).is_err());
+    }
+
+    #[test]
+    fn scores_overflow() {
+        let line = |n: u32| {
+            let numbers = (1..=n).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+            format!("Card 1: {} | {}", numbers, numbers)
+        };
+
+        let card: Card = line(64).parse().unwrap();
+        assert_eq!(card.score(), Ok(1 << 63));
+        let card: Card = line(65).parse().unwrap();
+        assert!(card.score().is_err());
+    }
 }
@@ -1,79 +1,126 @@
 use std::collections::HashMap;
 
 use lazy_static::lazy_static;
-use regex::Regex;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{
+        interval::{Interval as Range, RangeSet},
+        parse::{integers, LinePattern},
+    },
+};
 
 pub struct Instance;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Range {
-    from: u64,
-    length: u64,
+#[derive(Debug)]
+struct MapRange {
+    source: i64,
+    destination: i64,
+    range: i64,
 }
 
-impl Range {
-    fn from_length(from: u64, length: u64) -> Self {
-        Range { from, length }
+impl MapRange {
+    fn source_range(&self) -> Range {
+        Range::from_length(self.source, self.range)
     }
 
-    fn from_bounds(from: u64, to_excl: u64) -> Self {
-        Range {
-            from,
-            length: to_excl - from,
-        }
-    }
+    fn map_range(&self, r: &Range) -> (Vec<Range>, Option<Range>) {
+        let source = self.source_range();
+        let mapped = r
+            .intersect(&source)
+            .map(|m| m.shift(self.destination - self.source));
 
-    fn to_excl(&self) -> u64 {
-        self.from + self.length
+        let unmapped = RangeSet::from(*r).difference(&source.into());
+        (unmapped.iter().copied().collect(), mapped)
     }
 }
 
-#[derive(Debug)]
-struct MapRange {
-    source: u64,
-    destination: u64,
-    range: u64,
+// Values are only ever shifted within this domain, which leaves headroom for the offsets
+const DOMAIN: Range = Range {
+    start: i64::MIN / 4,
+    end: i64::MAX / 4,
+};
+
+// `length` values from `start`, None unless they all lie within DOMAIN
+fn domain_range(start: i64, length: i64) -> Option<Range> {
+    let end = start.checked_add(length)?;
+    (length >= 0 && DOMAIN.start <= start && end <= DOMAIN.end)
+        .then(|| Range::from_bounds(start, end))
 }
 
-impl MapRange {
-    fn source_end_excl(&self) -> u64 {
-        self.source + self.range
+// A total function on DOMAIN, identity outside the stored segments. Segments are sorted,
+// disjoint, never adjacent with the same offset and never have a zero offset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Piecewise(Vec<(Range, i64)>);
+
+impl Piecewise {
+    fn new(mut segments: Vec<(Range, i64)>) -> Self {
+        segments.retain(|(r, o)| !r.is_empty() && *o != 0);
+        segments.sort();
+
+        let mut coalesced: Vec<(Range, i64)> = Vec::with_capacity(segments.len());
+        for (r, o) in segments {
+            match coalesced.last_mut() {
+                Some((last, offset)) if last.end == r.start && *offset == o => last.end = r.end,
+                _ => coalesced.push((r, o)),
+            }
+        }
+        Piecewise(coalesced)
     }
 
-    fn try_map(&self, v: u64) -> Option<u64> {
-        if (self.source..self.source_end_excl()).contains(&v) {
-            Some(self.destination + v - self.source)
-        } else {
-            None
-        }
+    fn segments(&self) -> usize {
+        self.0.len()
     }
 
-    fn map_range(&self, r: &Range) -> (Vec<Range>, Option<Range>) {
-        let mut unmapped = Vec::new();
-        let mut mapped = None;
-        if r.from < self.source {
-            let to_excl = r.to_excl().min(self.source);
-            unmapped.push(Range::from_bounds(r.from, to_excl))
+    // Every segment including the identity gaps, covering all of DOMAIN
+    fn pieces(&self) -> Vec<(Range, i64)> {
+        let mut pieces = Vec::new();
+        let mut position = DOMAIN.start;
+        for &(r, o) in &self.0 {
+            pieces.push((Range::from_bounds(position, r.start), 0));
+            pieces.push((r, o));
+            position = r.end;
         }
+        pieces.push((Range::from_bounds(position, DOMAIN.end), 0));
+        pieces.retain(|(r, _)| !r.is_empty());
+        pieces
+    }
 
-        if r.to_excl() > self.source_end_excl() {
-            let from = r.from.max(self.source_end_excl());
-            unmapped.push(Range::from_bounds(from, r.to_excl()))
+    // Applies self and then next
+    fn then(&self, next: &Piecewise) -> Piecewise {
+        let next_pieces = next.pieces();
+        let mut segments = Vec::new();
+        for (r, o) in self.pieces() {
+            let image = r.shift(o);
+            for &(n, no) in &next_pieces {
+                if let Some(i) = image.intersect(&n) {
+                    segments.push((i.shift(-o), o + no));
+                }
+            }
         }
+        Piecewise::new(segments)
+    }
 
-        let from = r.from.max(self.source);
-        let to_excl = r.to_excl().min(self.source_end_excl());
-        let length = to_excl.checked_sub(from);
-
-        if let (Some(mapped_from), Some(length)) = (self.try_map(from), length) {
-            mapped = Some(Range::from_length(mapped_from, length))
-        }
+    fn image(&self, values: &RangeSet) -> RangeSet {
+        self.pieces().iter().fold(RangeSet::new(), |image, (r, o)| {
+            let shifted = values
+                .intersection(&(*r).into())
+                .iter()
+                .map(|i| i.shift(*o))
+                .collect();
+            image.union(&shifted)
+        })
+    }
 
-        (unmapped, mapped)
+    fn preimage(&self, values: &RangeSet) -> RangeSet {
+        self.pieces()
+            .iter()
+            .flat_map(|(r, o)| values.iter().filter_map(move |v| v.shift(-o).intersect(r)))
+            .collect()
     }
 }
+
 #[derive(Debug)]
 struct Map {
     to: String,
@@ -81,62 +128,55 @@ struct Map {
 }
 
 impl Map {
-    fn map_range(&self, range: Range) -> Vec<Range> {
-        let mut current_ranges = vec![range];
-        let mut all_mapped = Vec::new();
+    // Earlier ranges win where ranges overlap
+    fn piecewise(&self) -> Piecewise {
+        let mut unmapped = vec![DOMAIN];
+        let mut segments = Vec::new();
 
         for r in &self.ranges {
-            let mut new_ranges = Vec::new();
-            for c in &current_ranges {
-                let (unmapped, mapped) = r.map_range(c);
+            let offset = r.destination - r.source;
+            let mut new_unmapped = Vec::new();
+            for c in &unmapped {
+                let (rest, mapped) = r.map_range(c);
 
-                new_ranges.extend(unmapped);
-                all_mapped.extend(mapped);
+                new_unmapped.extend(rest);
+                segments.extend(mapped.map(|m| (m.shift(-offset), offset)));
             }
-            current_ranges = new_ranges;
+            unmapped = new_unmapped;
         }
 
-        all_mapped.extend(current_ranges);
-
-        all_mapped
+        Piecewise::new(segments)
     }
 }
 
 #[derive(Debug)]
 struct Almanac {
-    seeds: Vec<u64>,
+    seeds: Vec<i64>,
     maps: HashMap<String, Map>,
 }
 
 lazy_static! {
-    static ref NUMBERS_REGEX: Regex = Regex::new(r"\d+").unwrap();
-    static ref MAPPING_REGEX: Regex = Regex::new(r"(.+)-to-(.+) ").unwrap();
+    static ref MAPPING_PATTERN: LinePattern = LinePattern::new("{}-to-{} map:").unwrap();
 }
 
 impl Almanac {
     fn from_lines(lines: &[String]) -> Result<Almanac, String> {
-        let mut lines = lines.iter();
+        let mut lines = lines.iter().zip(1..);
 
-        let seeds_line = lines.next().ok_or("missing seeds")?;
-        let seeds: Vec<_> = NUMBERS_REGEX
-            .captures_iter(seeds_line)
-            .map(|i| i.extract::<0>().0.parse::<u64>().unwrap())
-            .collect();
+        let (seeds_line, _) = lines.next().ok_or("missing seeds")?;
+        let seeds = integers(seeds_line)?;
 
         lines.next();
 
         let mut maps = HashMap::new();
         let mut ranges = &mut Vec::new();
 
-        for line in lines {
+        for (line, n) in lines {
             if line.is_empty() {
                 continue;
-            } else if !line.chars().next().map_or(false, |c| c.is_ascii_digit()) {
-                let [from, to] = MAPPING_REGEX
-                    .captures(line)
-                    .ok_or(format!("Bad mapping: {}", line))?
-                    .extract()
-                    .1;
+            } else if !line.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+                let mapping = MAPPING_PATTERN.captures(line).map_err(|e| e.on_line(n))?;
+                let (from, to) = (mapping.str(0), mapping.str(1));
 
                 let map = maps.entry(from.to_owned()).or_insert(Map {
                     to: to.to_owned(),
@@ -144,14 +184,17 @@ impl Almanac {
                 });
                 ranges = &mut map.ranges;
             } else {
-                let mut numbers = NUMBERS_REGEX
-                    .captures_iter(line)
-                    .map(|i| i.extract::<0>().0.parse::<u64>().unwrap());
+                let mut numbers = integers(line).map_err(|e| e.on_line(n))?.into_iter();
                 let destination = numbers
                     .next()
                     .ok_or(format!("missing destination: {}", line))?;
                 let source = numbers.next().ok_or(format!("missing source: {}", line))?;
                 let range = numbers.next().ok_or(format!("missing range: {}", line))?;
+                if domain_range(source, range).is_none()
+                    || domain_range(destination, range).is_none()
+                {
+                    return Err(format!("map range outside {:?}: {}", DOMAIN, line));
+                }
 
                 let range = MapRange {
                     source,
@@ -165,60 +208,150 @@ impl Almanac {
         Ok(Almanac { seeds, maps })
     }
 
-    fn lowest_seeds(&self, seeds: Vec<Range>) -> u64 {
-        let mut current = "seed";
-
-        let mut current_values = seeds;
-
-        while let Some(map) = self.maps.get(current) {
-            let mut new_values = Vec::new();
-            for value in current_values {
-                new_values.extend(map.map_range(value));
+    // The stages from one category to another, None if the maps never lead there
+    fn stages<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<(&'a str, &'a Map)>> {
+        let mut stages = Vec::new();
+        let mut current = from;
+        while current != to {
+            let map = self.maps.get(current)?;
+            stages.push((current, map));
+            current = map.to.as_str();
+            if stages.len() > self.maps.len() {
+                return None;
             }
+        }
+        Some(stages)
+    }
 
-            current_values = new_values;
-            current = map.to.as_str();
+    fn composed(&self, from: &str, to: &str) -> Option<Piecewise> {
+        let stages = self.stages(from, to)?;
+        Some(
+            stages
+                .iter()
+                .fold(Piecewise::default(), |f, (_, m)| f.then(&m.piecewise())),
+        )
+    }
+
+    // Works in either direction, going backwards can give many values
+    fn lookup(&self, from: &str, to: &str, values: &RangeSet) -> Result<RangeSet, String> {
+        if let Some(f) = self.composed(from, to) {
+            Ok(f.image(values))
+        } else if let Some(f) = self.composed(to, from) {
+            Ok(f.preimage(values))
+        } else {
+            Err(format!("No maps between {} and {}", from, to))
         }
+    }
 
-        current_values.into_iter().map(|c| c.from).min().unwrap()
+    fn seed_to_location(&self) -> Result<Piecewise, String> {
+        self.composed("seed", "location")
+            .ok_or("No maps from seed to location".to_owned())
     }
 
-    fn lowest(&self) -> u64 {
-        let seeds: Vec<_> = self
-            .seeds
+    fn segment_report(&self) -> Vec<String> {
+        let stages = self.stages("seed", "location").unwrap_or_default();
+        let mut composed = Piecewise::default();
+        stages
             .iter()
-            .map(|a| Range::from_length(a.to_owned(), 1))
-            .collect();
+            .map(|(from, map)| {
+                let stage = map.piecewise();
+                composed = composed.then(&stage);
+                format!(
+                    "{}-to-{}: {} segments, {} composed",
+                    from,
+                    map.to,
+                    stage.segments(),
+                    composed.segments()
+                )
+            })
+            .collect()
+    }
 
-        self.lowest_seeds(seeds)
+    fn seeds(&self) -> Result<RangeSet, String> {
+        self.seeds
+            .iter()
+            .map(|&a| domain_range(a, 1).ok_or(format!("seed outside {:?}: {}", DOMAIN, a)))
+            .collect()
     }
 
-    fn lowest_range(&self) -> u64 {
-        let seeds: Vec<_> = self
-            .seeds
+    fn seed_ranges(&self) -> Result<RangeSet, String> {
+        self.seeds
             .chunks(2)
             .map(|c| match c {
-                [a, b] => Range::from_length(a.to_owned(), b.to_owned()),
-                _ => panic!(),
+                [a, b] => domain_range(*a, *b)
+                    .ok_or(format!("seed range outside {:?}: {} {}", DOMAIN, a, b)),
+                _ => Err("Seed ranges must come in pairs".to_owned()),
             })
-            .collect();
+            .collect()
+    }
+}
 
-        self.lowest_seeds(seeds)
+// Written as `from:to:value`, for example `location:seed:35`
+fn parse_lookup(s: &str) -> Result<(&str, &str, i64), String> {
+    let bad = || format!("Bad lookup: {:?}", s);
+    let mut parts = s.split(':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(from), Some(to), Some(value), None) => {
+            let value = value.parse().map_err(|_| bad())?;
+            domain_range(value, 1).ok_or_else(bad)?;
+            Ok((from, to, value))
+        }
+        _ => Err(bad()),
     }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let almanac = Almanac::from_lines(&lines)?;
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
 
-        let part1 = almanac.lowest().to_string();
-        let part2 = almanac.lowest_range().to_string();
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let almanac = Almanac::from_lines(&input.to_lines())?;
+        let location = almanac.seed_to_location()?;
+
+        let lowest = |seeds: &RangeSet| {
+            location
+                .image(seeds)
+                .min()
+                .ok_or("No seeds to plant".to_owned())
+        };
+        let part1 = lowest(&almanac.seeds()?)?;
+        let part2 = lowest(&almanac.seed_ranges()?)?;
+
+        if options.verbose {
+            for line in almanac.segment_report() {
+                println!("{}", line);
+            }
+        }
 
         Ok(DayResult {
-            part1,
-            part2: Some(part2),
+            part1: part1.to_string(),
+            part2: Some(part2.to_string()),
         })
     }
+
+    fn queries(
+        &self,
+        input: &Input,
+        options: &RunOptions,
+    ) -> Result<Vec<(String, String)>, String> {
+        let Some(lookup) = options.param("lookup") else {
+            return Ok(Vec::new());
+        };
+
+        let almanac = Almanac::from_lines(&input.to_lines())?;
+        let (from, to, value) = parse_lookup(lookup)?;
+        let found = almanac.lookup(from, to, &Range::from_length(value, 1).into())?;
+        let found: Vec<_> = found.iter().map(|r| (r.start, r.end)).collect();
+        Ok(vec![(
+            format!("{} {} is {}", from, value, to),
+            format!("{:?}", found),
+        )])
+    }
+
+    fn version(&self) -> u32 {
+        3
+    }
 }
 
 #[cfg(test)]
@@ -316,9 +449,90 @@ mod tests {
         };
 
         let (unmapped, mapped) = map_range.map_range(&range);
-        assert!(unmapped.len() == 0);
+        assert!(unmapped.is_empty());
         assert!(mapped.is_some());
 
         assert_eq!(mapped.unwrap(), Range::from_length(45, 5));
     }
+
+    #[test]
+    fn composes_and_inverts() {
+        let first = Piecewise::new(vec![
+            (Range::from_bounds(0, 10), 5),
+            (Range::from_bounds(10, 20), -10),
+        ]);
+        let second = Piecewise::new(vec![(Range::from_bounds(0, 10), 5)]);
+
+        // 0..5 lands on 5..10 and then 10..15, 5..10 leaves second alone
+        let composed = first.then(&second);
+        assert_eq!(
+            composed,
+            Piecewise(vec![
+                (Range::from_bounds(0, 5), 10),
+                (Range::from_bounds(5, 10), 5),
+                (Range::from_bounds(10, 20), -5),
+            ])
+        );
+
+        let coalesced = Piecewise::new(vec![
+            (Range::from_bounds(5, 8), 3),
+            (Range::from_bounds(0, 5), 3),
+            (Range::from_bounds(8, 9), 0),
+        ]);
+        assert_eq!(coalesced, Piecewise(vec![(Range::from_bounds(0, 8), 3)]));
+
+        let seeds = composed.preimage(&Range::from_bounds(10, 12).into());
+        assert_eq!(
+            seeds,
+            [
+                Range::from_bounds(0, 2),
+                Range::from_bounds(5, 7),
+                Range::from_bounds(15, 17)
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert_eq!(composed.image(&seeds), Range::from_bounds(10, 12).into());
+    }
+
+    #[test]
+    fn looks_up_without_verbose() {
+        let input = Input::read("fixtures/day5.txt").unwrap();
+        let options = RunOptions {
+            params: vec![("lookup".to_owned(), "location:seed:46".to_owned())],
+            ..RunOptions::default()
+        };
+
+        assert_eq!(
+            Instance.queries(&input, &options).unwrap(),
+            vec![("location 46 is seed".to_owned(), "[(82, 83)]".to_owned())]
+        );
+    }
+
+    #[test]
+    fn rejects_values_outside_the_domain() {
+        let almanac = |seeds: &str, range: &str| {
+            let lines: Vec<String> = [seeds, "", "seed-to-location map:", range]
+                .iter()
+                .map(|l| l.to_string())
+                .collect();
+            Almanac::from_lines(&lines)
+        };
+
+        assert!(almanac("seeds: 1 2", "50 98 2").is_ok());
+        let edge = DOMAIN.end - 2;
+        assert!(almanac("seeds: 1 2", &format!("{} 98 2", edge)).is_ok());
+        assert!(almanac("seeds: 1 2", &format!("{} 98 3", edge)).is_err());
+        assert!(almanac("seeds: 1 2", &format!("50 {} 3", edge)).is_err());
+        assert!(almanac("seeds: 1 2", &format!("50 98 {}", i64::MAX)).is_err());
+        assert!(almanac("seeds: 1 2", "50 98 -2").is_err());
+
+        let seeds = almanac(&format!("seeds: 1 {}", DOMAIN.end), "50 98 2").unwrap();
+        assert!(seeds.seeds().is_err());
+        assert!(seeds.seed_ranges().is_err());
+        let seeds = almanac(&format!("seeds: 2 {}", DOMAIN.end - 1), "50 98 2").unwrap();
+        assert!(seeds.seeds().is_ok());
+        assert!(seeds.seed_ranges().is_err());
+        assert!(parse_lookup(&format!("seed:location:{}", DOMAIN.start - 1)).is_err());
+    }
 }
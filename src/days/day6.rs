@@ -1,10 +1,12 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::parse::integers,
+};
 
 pub struct Instance;
 
+// Inputs are u64 so every distance, up to the peak, fits in a u128
 #[derive(Debug)]
 struct Race {
     time: u64,
@@ -12,57 +14,150 @@ struct Race {
 }
 
 impl Race {
-    fn wins(&self) -> usize {
-        (1..self.time)
-            .rev()
-            .zip(1..self.time)
-            .filter(|(remaining, speed)| remaining * speed > self.distance)
-            .count()
+    fn beats(&self, hold: u64) -> bool {
+        hold as u128 * (self.time - hold) as u128 > self.distance as u128
+    }
+
+    // Inclusive hold times that win. The distance peaks at time / 2 and is symmetric about it,
+    // so only the first winning hold is solved for
+    fn winning_holds(&self) -> Option<(u64, u64)> {
+        let half = self.time / 2;
+        let peak = half as u128 * (self.time - half) as u128;
+        let margin = peak.checked_sub(self.distance as u128 + 1)?;
+
+        // Holding half - j wins while j * j (+ j for odd times) stays under the margin, the
+        // root is at most one hold too far from the peak for odd times
+        let mut first = half - (margin.isqrt() as u64).min(half);
+        if !self.beats(first) {
+            first += 1;
+        }
+
+        Some((first, self.time - first))
+    }
+
+    fn wins(&self) -> u64 {
+        self.winning_holds()
+            .map_or(0, |(first, last)| last - first + 1)
+    }
+
+    fn wins_reference(&self) -> u64 {
+        (0..=self.time).filter(|&h| self.beats(h)).count() as u64
     }
 }
 
-lazy_static! {
-    static ref NUMBERS_REGEX: Regex = Regex::new(r"\d+").unwrap();
+fn line(lines: &[String], n: usize) -> Result<&str, String> {
+    lines
+        .get(n - 1)
+        .map(|l| l.as_str())
+        .ok_or(format!("Missing line {}", n))
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let times = NUMBERS_REGEX
-            .captures_iter(&lines[0])
-            .map(|i| i.extract::<0>().0.parse::<u64>().unwrap());
-        let distances = NUMBERS_REGEX
-            .captures_iter(&lines[1])
-            .map(|i| i.extract::<0>().0.parse::<u64>().unwrap());
-
-        let races: Vec<_> = times
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
+        let (time_line, distance_line) = (line(&lines, 1)?, line(&lines, 2)?);
+        let times = integers::<u64>(time_line).map_err(|e| e.on_line(1))?;
+        let distances = integers::<u64>(distance_line).map_err(|e| e.on_line(2))?;
+
+        let mut races: Vec<_> = times
+            .into_iter()
             .zip(distances)
             .map(|(time, distance)| Race { time, distance })
             .collect();
 
-        let mut part1 = 1;
-        for race in races {
-            part1 *= race.wins();
-        }
+        let joined = |l: &str, n: usize| {
+            integers::<u64>(&l.replace(' ', ""))
+                .map_err(|e| e.on_line(n))?
+                .first()
+                .copied()
+                .ok_or(format!("No number on line {}", n))
+        };
+        let real_time = joined(time_line, 1)?;
+        let real_distance = joined(distance_line, 2)?;
 
-        let real_time = NUMBERS_REGEX
-            .captures_iter(&lines[0].replace(' ', ""))
-            .map(|i| i.extract::<0>().0.parse::<u64>().unwrap())
-            .next()
-            .unwrap();
-        let real_distance = NUMBERS_REGEX
-            .captures_iter(&lines[1].replace(' ', ""))
-            .map(|i| i.extract::<0>().0.parse::<u64>().unwrap())
-            .next()
-            .unwrap();
-
-        let real_race = Race {
+        races.push(Race {
             time: real_time,
             distance: real_distance,
-        };
+        });
+
+        if options.verbose {
+            for race in &races {
+                println!("{:?}: {:?}", race, race.winning_holds());
+            }
+        }
+
+        let wins: Vec<_> = races
+            .iter()
+            .map(|r| {
+                if options.reference {
+                    r.wins_reference()
+                } else {
+                    r.wins()
+                }
+            })
+            .collect();
+        let (real, wins) = wins.split_last().unwrap();
+
+        let wins = wins
+            .iter()
+            .try_fold(1u64, |product, &w| product.checked_mul(w))
+            .ok_or("Overflow multiplying the ways to win")?;
 
         Ok(DayResult {
-            part1: part1.to_string(),
-            part2: Some(real_race.wins().to_string()),
+            part1: wins.to_string(),
+            part2: Some(real.to_string()),
         })
     }
+
+    fn version(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_brute_force() {
+        for time in 0..60 {
+            for distance in 0..=time * time / 4 + 2 {
+                let race = Race { time, distance };
+                assert_eq!(race.wins(), race.wins_reference(), "{:?}", race);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_long_races() {
+        for time in [u32::MAX as u64, 1 << 40, u64::MAX - 1, u64::MAX] {
+            for distance in [0, 1 << 40, u64::MAX / 5, u64::MAX - 1, u64::MAX] {
+                let race = Race { time, distance };
+                match race.winning_holds() {
+                    Some((first, last)) => {
+                        assert!(race.beats(first) && !race.beats(first - 1), "{:?}", race);
+                        assert!(race.beats(last) && !race.beats(last + 1), "{:?}", race);
+                    }
+                    None => assert!(!race.beats(time / 2), "{:?}", race),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let run = |lines: &[&str]| {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            Instance.run(lines)
+        };
+
+        assert!(run(&["Time: 7 15 30", "Distance: 9 40 200"]).is_ok());
+        assert!(run(&["Time: 7 15 30"]).is_err());
+        assert!(run(&["Time:", "Distance:"]).is_err());
+        assert!(run(&["Time: 99999999999999999999", "Distance: 1"]).is_err());
+    }
 }
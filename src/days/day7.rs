@@ -1,202 +1,201 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{cmp::Reverse, str::FromStr};
 
 use itertools::Itertools;
 
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+};
 
 pub struct Instance;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
-enum Card {
-    Joker,
-    Number(u8),
-    Jack,
-    Queen,
-    King,
-    Ace,
+// Group sizes in a hand, largest first, such as [3, 2] for a full house
+type Shape = Vec<usize>;
+
+#[derive(Debug, Clone)]
+struct Rules {
+    // Weakest first, used to break ties between hands of the same category
+    order: Vec<char>,
+    wild: Vec<char>,
+    hand_size: usize,
+    // Weakest first
+    categories: Vec<Shape>,
 }
 
-impl Card {
-    fn from_char(c: char) -> Self {
-        match c {
-            'A' => Card::Ace,
-            'K' => Card::King,
-            'Q' => Card::Queen,
-            'J' => Card::Jack,
-            'T' => Card::Number(10),
-            n => Card::Number(n.to_digit(10).unwrap() as u8),
+// Every way to split n cards into groups, ordered so that bigger groups beat smaller ones,
+// which for five cards is high card, one pair, two pair, three of a kind and so on
+fn partitions(n: usize) -> Vec<Shape> {
+    fn extend(remaining: usize, max: usize, shape: &mut Shape, all: &mut Vec<Shape>) {
+        if remaining == 0 {
+            all.push(shape.clone());
+        }
+        for size in (1..=remaining.min(max)).rev() {
+            shape.push(size);
+            extend(remaining - size, size, shape, all);
+            shape.pop();
         }
     }
-}
 
-#[derive(Debug)]
-struct Hand {
-    cards: Vec<Card>,
-    bid: u32,
+    let mut all = Vec::new();
+    extend(n, n, &mut Vec::new(), &mut all);
+    all.sort();
+    all
 }
 
-impl FromStr for Hand {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cards, bid) = s.split_once(' ').ok_or("Missing space")?;
-        let cards = cards.chars().map(Card::from_char).collect();
-        let bid = bid.parse::<u32>().map_err(|e| format!("{}: {}", e, bid))?;
-
-        Ok(Hand { cards, bid })
-    }
-}
-
-impl PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.cards == other.cards && self.bid == other.bid
+impl Rules {
+    fn new(order: &str, wild: &str, hand_size: usize) -> Self {
+        Rules {
+            order: order.chars().collect(),
+            wild: wild.chars().collect(),
+            hand_size,
+            categories: partitions(hand_size),
+        }
     }
-}
-
-impl Eq for Hand {}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    fn named(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "standard" => Rules::new("23456789TJQKA", "", 5),
+            "jokers" => Rules::new("J23456789TQKA", "J", 5),
+            "wild-twos" => Rules::new("2J3456789TQKA", "2J", 5),
+            "six-card" => Rules::new("23456789TJQKA", "", 6),
+            "six-card-jokers" => Rules::new("J23456789TQKA", "J", 6),
+            _ => Err(format!("Unknown rules: {}", name))?,
+        })
     }
-}
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.type_rank().cmp(&other.type_rank()) {
-            core::cmp::Ordering::Equal => {}
-            ord => return ord,
-        }
-
-        match self.cards.cmp(&other.cards) {
-            core::cmp::Ordering::Equal => {}
-            ord => return ord,
-        }
-        self.bid.cmp(&other.bid)
+    fn strength(&self, card: char) -> Option<usize> {
+        self.order.iter().position(|&c| c == card)
     }
-}
 
-impl Hand {
-    fn card_counts(&self) -> HashMap<&Card, usize> {
-        self.cards
+    fn shape(&self, cards: &[char]) -> Shape {
+        cards
             .iter()
             .sorted()
-            .group_by(|s| *s)
-            .into_iter()
-            .map(|(c, g)| (c, g.count()))
+            .dedup_with_count()
+            .map(|(n, _)| n)
+            .sorted_by_key(|&n| Reverse(n))
             .collect()
     }
 
-    fn is_five_of_kind(&self) -> bool {
-        self.max_with_jokers() == 5
-    }
-
-    fn is_four_of_kind(&self) -> bool {
-        self.max_with_jokers() == 4
-    }
-
-    fn is_three_of_kind(&self) -> bool {
-        !self.is_full_house() && self.max_with_jokers() == 3
+    fn rank(&self, shape: &Shape) -> usize {
+        self.categories
+            .iter()
+            .position(|c| c == shape)
+            .expect("categories cover every shape")
     }
 
-    fn is_full_house(&self) -> bool {
-        if self.joker_count() > 1 {
-            return false;
+    // Wild cards all join the biggest group
+    fn category(&self, cards: &[char]) -> usize {
+        let (wild, rest): (Vec<_>, Vec<_>) = cards.iter().partition(|c| self.wild.contains(c));
+        let mut shape = self.shape(&rest);
+        match shape.first_mut() {
+            Some(biggest) => *biggest += wild.len(),
+            None => shape.push(wild.len()),
         }
+        self.rank(&shape)
+    }
 
-        if self.joker_count() == 1 {
-            let mut counts = self.card_counts();
-            counts.remove(&Card::Joker);
-            return self.card_counts().values().filter(|l| **l == 2).count() == 2;
+    // Tries every substitution of the wild cards
+    fn category_brute_force(&self, cards: &[char]) -> usize {
+        let (wild, rest): (Vec<_>, Vec<_>) = cards.iter().partition(|c| self.wild.contains(c));
+        if wild.is_empty() {
+            return self.rank(&self.shape(cards));
         }
-
-        self.card_counts().values().max() == Some(&3)
-            && self.card_counts().values().min() == Some(&2)
+        self.order
+            .iter()
+            .combinations_with_replacement(wild.len())
+            .map(|substitutes| {
+                let cards: Vec<char> = rest.iter().chain(substitutes).copied().collect();
+                self.rank(&self.shape(&cards))
+            })
+            .max()
+            .unwrap()
     }
+}
 
-    fn is_two_pair(&self) -> bool {
-        if self.joker_count() > 0 {
-            return false;
-        }
+#[derive(Debug)]
+struct Hand {
+    cards: Vec<char>,
+    bid: u32,
+}
 
-        self.card_counts().values().filter(|l| **l == 2).count() == 2
-    }
+impl FromStr for Hand {
+    type Err = String;
 
-    fn is_pair(&self) -> bool {
-        !self.is_two_pair() && !self.is_full_house() && self.max_with_jokers() == 2
-    }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (cards, bid) = s.split_once(' ').ok_or("Missing space")?;
+        let cards = cards.chars().collect();
+        let bid = bid.parse::<u32>().map_err(|e| format!("{}: {}", e, bid))?;
 
-    fn type_rank(&self) -> u8 {
-        if self.is_pair() {
-            return 1;
-        }
-        if self.is_two_pair() {
-            return 2;
-        }
-        if self.is_three_of_kind() {
-            return 3;
-        }
-        if self.is_full_house() {
-            return 4;
-        }
-        if self.is_four_of_kind() {
-            return 5;
-        }
-        if self.is_five_of_kind() {
-            return 6;
-        }
-        0
+        Ok(Hand { cards, bid })
     }
+}
 
-    fn with_jokers(&self) -> Self {
-        let cards = self
+impl Hand {
+    fn key(&self, rules: &Rules, reference: bool) -> Result<(usize, Vec<usize>), String> {
+        let cards: String = self.cards.iter().collect();
+        if self.cards.len() != rules.hand_size {
+            return Err(format!(
+                "Hand {} should have {} cards",
+                cards, rules.hand_size
+            ));
+        }
+        let strengths = self
             .cards
             .iter()
-            .map(|&c| if c == Card::Jack { Card::Joker } else { c })
-            .collect();
-        Hand {
-            cards,
-            bid: self.bid,
-        }
-    }
+            .map(|&c| rules.strength(c))
+            .collect::<Option<_>>()
+            .ok_or(format!("Unknown card in {}", cards))?;
 
-    fn joker_count(&self) -> usize {
-        self.card_counts().get(&Card::Joker).copied().unwrap_or(0)
-    }
-
-    fn max_with_jokers(&self) -> usize {
-        let max = self
-            .card_counts()
-            .iter()
-            .filter(|(c, _)| ***c != Card::Joker)
-            .map(|(_, i)| *i)
-            .max()
-            .unwrap_or(0);
-        max + self.joker_count()
+        let category = if reference {
+            rules.category_brute_force(&self.cards)
+        } else {
+            rules.category(&self.cards)
+        };
+        Ok((category, strengths))
     }
 }
 
-fn total_winnings(hands: &[Hand]) -> u32 {
-    let mut vec: Vec<_> = hands.iter().collect();
-    vec.sort();
-    let ranked: Vec<_> = vec.iter().zip(1..).collect();
-    ranked.iter().map(|(h, r)| h.bid * r).sum()
+fn total_winnings(hands: &[Hand], rules: &Rules, reference: bool) -> Result<u32, String> {
+    let keys: Vec<_> = hands
+        .iter()
+        .map(|h| h.key(rules, reference))
+        .try_collect()?;
+    let ranked = hands
+        .iter()
+        .zip(keys)
+        .sorted_by(|(a, ka), (b, kb)| ka.cmp(kb).then(a.bid.cmp(&b.bid)));
+    Ok(ranked.zip(1..).map(|((h, _), r)| h.bid * r).sum())
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
-        let parsed: Vec<_> = lines
-            .iter()
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let parsed: Vec<_> = input
+            .lines()
             .map(|l| l.parse::<Hand>())
             .collect::<Result<_, _>>()?;
 
-        let with_jokers: Vec<_> = parsed.iter().map(|h| h.with_jokers()).collect();
+        // One rule set per part, for example `standard,jokers`
+        let names = options.param("rules").unwrap_or("standard,jokers");
+        let (part1, part2) = names
+            .split_once(',')
+            .ok_or(format!("Expected two rule sets, got {}", names))?;
 
         Ok(DayResult {
-            part1: total_winnings(&parsed).to_string(),
-            part2: Some(total_winnings(&with_jokers).to_string()),
+            part1: total_winnings(&parsed, &Rules::named(part1)?, options.reference)?.to_string(),
+            part2: Some(
+                total_winnings(&parsed, &Rules::named(part2)?, options.reference)?.to_string(),
+            ),
         })
     }
+
+    fn version(&self) -> u32 {
+        2
+    }
 }
 
 #[cfg(test)]
@@ -205,11 +204,38 @@ mod tests {
 
     #[test]
     fn five_of_a_kind() {
-        assert!(dbg!(Hand::from_str("JJJJJ 0").unwrap().with_jokers().type_rank()) == 6);
-        assert!(dbg!(Hand::from_str("JJJJA 0").unwrap().with_jokers().type_rank()) == 6);
-        assert!(dbg!(Hand::from_str("JJAAJ 0").unwrap().with_jokers().type_rank()) == 6);
-        assert!(dbg!(Hand::from_str("AJAJA 0").unwrap().with_jokers().type_rank()) == 6);
-        assert!(dbg!(Hand::from_str("AAAAJ 0").unwrap().with_jokers().type_rank()) == 6);
-        assert!(dbg!(Hand::from_str("AAAAA 0").unwrap().with_jokers().type_rank()) == 6);
+        let rules = Rules::named("jokers").unwrap();
+        let five = rules.categories.len() - 1;
+        for hand in ["JJJJJ", "JJJJA", "JJAAJ", "AJAJA", "AAAAJ", "AAAAA"] {
+            let cards: Vec<_> = hand.chars().collect();
+            assert_eq!(rules.category(&cards), five, "{}", hand);
+        }
+    }
+
+    #[test]
+    fn wild_cards_match_brute_force() {
+        for name in [
+            "standard",
+            "jokers",
+            "wild-twos",
+            "six-card",
+            "six-card-jokers",
+        ] {
+            let rules = Rules::named(name).unwrap();
+            for cards in rules
+                .order
+                .iter()
+                .copied()
+                .combinations_with_replacement(rules.hand_size)
+            {
+                assert_eq!(
+                    rules.category(&cards),
+                    rules.category_brute_force(&cards),
+                    "{} {:?}",
+                    name,
+                    cards
+                );
+            }
+        }
     }
 }
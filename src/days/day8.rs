@@ -1,14 +1,21 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
+use lazy_static::lazy_static;
 
 use super::{
-    day::{Day, DayResult},
-    util::lcm,
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+    util::{crt, graph::Graph, parse::LinePattern},
 };
 
 pub struct Instance;
 
+// Every combination of the ghosts' Z offsets is a candidate, so cap how many are tracked
+const MAX_RESIDUES: usize = 100_000;
+// Beyond this the reference walk takes too long to be useful
+const MAX_REFERENCE_STEPS: usize = 1_000_000;
+
 enum Instruction {
     Left,
     Right,
@@ -24,21 +31,46 @@ impl Instruction {
     }
 }
 
+// Steps at which a ghost is on a Z node, past the pre-period they repeat every period
+#[derive(Debug)]
+struct GhostCycle {
+    pre_period: usize,
+    period: usize,
+    early: Vec<usize>,
+    offsets: Vec<usize>,
+}
+
+impl GhostCycle {
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.pre_period {
+            self.early.contains(&step)
+        } else {
+            let offset = self.pre_period + (step - self.pre_period) % self.period;
+            self.offsets.contains(&offset)
+        }
+    }
+}
+
 struct Network(HashMap<String, (String, String)>);
 
+lazy_static! {
+    static ref NODE_PATTERN: LinePattern = LinePattern::new("{} = ({}, {})").unwrap();
+}
+
 impl Network {
-    fn new(lines: &[String]) -> Self {
+    fn new(lines: &[String]) -> Result<Self, String> {
         let mut map = HashMap::new();
 
-        for line in lines {
-            let from = line[0..3].to_owned();
-            let left = line[7..10].to_owned();
-            let right = line[12..15].to_owned();
+        for (line, n) in lines.iter().zip(3..) {
+            let node = NODE_PATTERN.captures(line).map_err(|e| e.on_line(n))?;
 
-            map.insert(from, (left, right));
+            map.insert(
+                node.str(0).to_owned(),
+                (node.str(1).to_owned(), node.str(2).to_owned()),
+            );
         }
 
-        Network(map)
+        Ok(Network(map))
     }
 
     fn traverse(&self, instructions: &[Instruction]) -> usize {
@@ -57,6 +89,30 @@ impl Network {
         steps
     }
 
+    fn graph(&self, instructions: &[Instruction]) -> Graph {
+        let mut graph = Graph::new(true);
+        for (node, (left, right)) in &self.0 {
+            let n = graph.node(node);
+            if node.ends_with('A') {
+                n.kind("start");
+            } else if node.ends_with('Z') {
+                n.kind("end");
+            }
+            graph.edge(node, left).label("L");
+            graph.edge(node, right).label("R");
+        }
+
+        if self.0.contains_key("AAA") {
+            let mut path = vec!["AAA"];
+            let mut instructions = instructions.iter().cycle();
+            while path.last() != Some(&"ZZZ") && path.len() <= self.0.len() * 2 {
+                path.push(self.step(path.last().unwrap(), instructions.next().unwrap()));
+            }
+            graph.highlight_path(&path);
+        }
+        graph
+    }
+
     fn step(&self, current: &str, instruction: &Instruction) -> &str {
         let (left, right) = &self.0[current];
         match instruction {
@@ -65,58 +121,217 @@ impl Network {
         }
     }
 
-    fn traverse_ghost(&self, instructions: &[Instruction]) -> usize {
+    // Follows one ghost over (node, instruction index) states until a state repeats
+    fn ghost_cycle(&self, start: &str, instructions: &[Instruction]) -> GhostCycle {
+        let mut seen = HashMap::new();
+        let mut hits = Vec::new();
+        let mut current = start;
+        let mut steps = 0;
+
+        let pre_period = loop {
+            let state = (current, steps % instructions.len());
+            if let Some(&first) = seen.get(&state) {
+                break first;
+            }
+            seen.insert(state, steps);
+            if current.ends_with('Z') {
+                hits.push(steps);
+            }
+            current = self.step(current, &instructions[state.1]);
+            steps += 1;
+        };
+
+        let period = steps - pre_period;
+        let (early, offsets) = hits.into_iter().partition(|&h| h < pre_period);
+        GhostCycle {
+            pre_period,
+            period,
+            early,
+            offsets,
+        }
+    }
+
+    fn traverse_ghost(&self, instructions: &[Instruction]) -> Result<usize, String> {
+        let starts: Vec<_> = self
+            .0
+            .keys()
+            .filter(|k| k.ends_with('A'))
+            .map(|s| s.as_str())
+            .sorted()
+            .collect();
+        if starts.is_empty() {
+            return Err("No ghosts start on an A node".to_owned());
+        }
+        let cycles: Vec<_> = starts
+            .iter()
+            .map(|s| self.ghost_cycle(s, instructions))
+            .collect();
+
+        // Before every ghost is looping the early hits can be checked directly
+        let settled = cycles.iter().map(|c| c.pre_period).max().unwrap_or(0);
+        if let Some(step) = (1..settled).find(|&step| cycles.iter().all(|c| c.is_hit(step))) {
+            return Ok(step);
+        }
+
+        // Ghosts with the fewest offsets go first, and combinations that can never line up
+        // are dropped after each ghost, so the candidates grow as slowly as possible
+        let mut residues = vec![(0, 1)];
+        for cycle in cycles.iter().sorted_by_key(|c| c.offsets.len()) {
+            let period = cycle.period as i128;
+            let combined: Vec<_> = residues
+                .iter()
+                .cartesian_product(&cycle.offsets)
+                .map(|(&r, &o)| crt(r, (o as i128 % period, period)))
+                .flatten_ok()
+                .try_collect()?;
+            residues = combined.into_iter().unique().collect();
+            if residues.len() > MAX_RESIDUES {
+                return Err(format!(
+                    "Ghosts from {} line up in over {} ways",
+                    starts.join(", "),
+                    MAX_RESIDUES
+                ));
+            }
+        }
+
+        residues
+            .iter()
+            .map(|&(r, m)| {
+                let from = settled.max(1) as i128;
+                from + (r - from).rem_euclid(m)
+            })
+            .min()
+            .map(|step| step as usize)
+            .ok_or(format!(
+                "Ghosts from {} are never all on Z at once",
+                starts.join(", ")
+            ))
+    }
+
+    fn traverse_ghost_reference(&self, instructions: &[Instruction]) -> Result<usize, String> {
         let mut ghosts: Vec<_> = self
             .0
             .keys()
             .filter(|k| k.ends_with('A'))
             .map(|s| s.as_str())
             .collect();
+        if ghosts.is_empty() {
+            return Err("No ghosts start on an A node".to_owned());
+        }
         let mut instructions = instructions.iter().cycle();
 
         let mut steps = 0;
 
-        let mut pattern = vec![Vec::new(); ghosts.len()];
-
-        while pattern.iter().filter(|g| g.len() >= 3).count() != ghosts.len() {
+        while !ghosts.iter().all(|g| g.ends_with('Z')) {
+            if steps == MAX_REFERENCE_STEPS {
+                return Err(format!(
+                    "Ghosts are not all on Z after {} steps, the reference stops there",
+                    MAX_REFERENCE_STEPS
+                ));
+            }
             let instruction = instructions.next().unwrap();
-            steps += 1;
-
-            for (i, ghost) in ghosts.iter_mut().enumerate() {
-                *ghost = &mut self.step(ghost, instruction);
-
-                if ghost.ends_with('Z') {
-                    pattern[i].push(steps)
-                }
+            for ghost in ghosts.iter_mut() {
+                *ghost = self.step(ghost, instruction);
             }
+            steps += 1;
         }
 
-        pattern.iter().for_each(|v| {
-            let diffs: Vec<_> = v
-                .windows(2)
-                .map(|w| match w {
-                    [a, b] => b - a,
-                    _ => unreachable!(),
-                })
-                .collect();
-            assert_eq!(v[0], diffs[0]);
-            assert!(diffs.iter().all_equal());
-        });
-
-        // This only works because they cycle back to the start and all have 1 destination
-        pattern.iter().map(|v| v[0]).fold(1, lcm)
+        Ok(steps)
     }
 }
 
 impl Day for Instance {
     fn run(&self, lines: Vec<String>) -> Result<DayResult, String> {
+        self.run_input(&lines.into(), &RunOptions::default())
+    }
+
+    fn run_input(&self, input: &Input, options: &RunOptions) -> Result<DayResult, String> {
+        let lines = input.to_lines();
         let instructions: Vec<_> = lines[0].chars().map(Instruction::from_char).collect();
 
-        let network = Network::new(&lines[2..]);
+        let network = Network::new(&lines[2..])?;
+        if let Some(path) = &options.graph {
+            network.graph(&instructions).save(path)?;
+        }
+
+        let part2 = if options.reference {
+            network.traverse_ghost_reference(&instructions)?
+        } else {
+            network.traverse_ghost(&instructions)?
+        };
 
         Ok(DayResult {
             part1: network.traverse(&instructions).to_string(),
-            part2: Some(network.traverse_ghost(&instructions).to_string()),
+            part2: Some(part2.to_string()),
         })
     }
+
+    fn version(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(lines: &[&str]) -> Network {
+        let lines: Vec<_> = lines.iter().map(|l| l.to_string()).collect();
+        Network::new(&lines).unwrap()
+    }
+
+    #[test]
+    fn ghosts_with_pre_periods() {
+        let mut lines = vec![
+            "1A = (1B, 1B)",
+            "1B = (1C, 1C)",
+            "1C = (1Z, 1Z)",
+            "1Z = (1C, 1C)",
+            "2A = (2Z, 2Z)",
+            "2Z = (2B, 2B)",
+            "2B = (2C, 2C)",
+            "2C = (2Z, 2Z)",
+        ];
+        let instructions = [Instruction::Left];
+
+        let ghosts = network(&lines);
+        assert_eq!(ghosts.traverse_ghost(&instructions), Ok(7));
+        assert_eq!(ghosts.traverse_ghost_reference(&instructions), Ok(7));
+
+        // Ghost 1 is on Z at odd steps from 3, ghost 3 at even ones
+        lines.truncate(4);
+        lines.extend(["3A = (3B, 3B)", "3B = (3Z, 3Z)", "3Z = (3B, 3B)"]);
+        let ghosts = network(&lines);
+        assert!(ghosts.traverse_ghost(&instructions).is_err());
+        let error = ghosts.traverse_ghost_reference(&instructions).unwrap_err();
+        assert!(error.starts_with("Ghosts are not all on Z"), "{}", error);
+    }
+
+    #[test]
+    fn no_ghosts() {
+        let lines = ["1B = (1Z, 1Z)", "1Z = (1B, 1B)"];
+        let instructions = [Instruction::Left];
+
+        let ghosts = network(&lines);
+        assert!(ghosts.traverse_ghost(&instructions).is_err());
+        assert!(ghosts.traverse_ghost_reference(&instructions).is_err());
+    }
+
+    #[test]
+    fn reference_matches_fast_path() {
+        let ghosts = network(&[
+            "11A = (11B, XXX)",
+            "11B = (XXX, 11Z)",
+            "11Z = (11B, XXX)",
+            "22A = (22B, XXX)",
+            "22B = (22C, 22C)",
+            "22C = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+            "XXX = (XXX, XXX)",
+        ]);
+        let instructions = [Instruction::Left, Instruction::Right];
+
+        assert_eq!(ghosts.traverse_ghost(&instructions), Ok(6));
+        assert_eq!(ghosts.traverse_ghost_reference(&instructions), Ok(6));
+    }
 }
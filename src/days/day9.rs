@@ -1,4 +1,7 @@
-use super::day::{Day, DayResult};
+use super::{
+    day::{Day, DayResult},
+    util::poly::Polynomial,
+};
 
 pub struct Instance;
 
@@ -6,47 +9,12 @@ pub struct Instance;
 struct History(Vec<i64>);
 
 impl History {
-    fn predict_forward(&self) -> i64 {
-        self.predict(true)
+    fn predict_forward(&self) -> Result<i64, String> {
+        Polynomial::settle(&self.0)?.at_index(self.0.len() as i64)
     }
 
-    fn predict_backward(&self) -> i64 {
-        self.predict(false)
-    }
-
-    fn predict(&self, forward: bool) -> i64 {
-        let mut deltas = Vec::new();
-        let mut current = self.0.clone();
-        loop {
-            let diffs: Vec<_> = current
-                .windows(2)
-                .map(|d| match d {
-                    [a, b] => b - a,
-                    v => panic!("{:?}", v),
-                })
-                .collect();
-
-            if diffs.iter().all(|&v| v == 0) {
-                break;
-            }
-
-            if forward {
-                deltas.push(*diffs.last().unwrap());
-            } else {
-                deltas.push(*diffs.first().unwrap());
-            }
-            current = diffs;
-        }
-
-        let delta: i64 = deltas
-            .iter()
-            .rev()
-            .fold(0, |a, b| if forward { b + a } else { b - a });
-        if forward {
-            self.0.last().unwrap() + delta
-        } else {
-            self.0.first().unwrap() - delta
-        }
+    fn predict_backward(&self) -> Result<i64, String> {
+        Polynomial::settle(&self.0)?.at_index(-1)
     }
 }
 
@@ -60,13 +28,13 @@ impl Day for Instance {
         let part1 = histories
             .iter()
             .map(|v| v.predict_forward())
-            .sum::<i64>()
+            .sum::<Result<i64, _>>()?
             .to_string();
 
         let part2 = histories
             .iter()
             .map(|v| v.predict_backward())
-            .sum::<i64>()
+            .sum::<Result<i64, _>>()?
             .to_string();
         Ok(DayResult {
             part1,
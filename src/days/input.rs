@@ -0,0 +1,52 @@
+use std::{fs, io, path::Path};
+
+pub struct Input(String);
+
+impl Input {
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::read_to_string(path).map(Input)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.0.lines()
+    }
+
+    pub fn byte_lines(&self) -> impl Iterator<Item = &[u8]> {
+        let bytes = self.as_bytes();
+        bytes
+            .strip_suffix(b"\n")
+            .unwrap_or(bytes)
+            .split(|&b| b == b'\n')
+            .map(|l| l.strip_suffix(b"\r").unwrap_or(l))
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint_from(0xcbf29ce484222325)
+    }
+
+    // Continues from an earlier fingerprint, to cover several files with one hash
+    pub fn fingerprint_from(&self, seed: u64) -> u64 {
+        // FNV-1a, so hashes stay stable between builds
+        self.as_bytes()
+            .iter()
+            .fold(seed, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        self.lines().map(|l| l.to_owned()).collect()
+    }
+}
+
+impl From<Vec<String>> for Input {
+    fn from(lines: Vec<String>) -> Self {
+        Input(lines.join("\n"))
+    }
+}
@@ -1,6 +1,13 @@
+use std::collections::HashMap;
+
+use day::Day;
+
 pub mod day;
+pub mod input;
 mod util;
 
+pub use util::profile;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
@@ -28,3 +35,6 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+
+// Every src/days/dayN.rs is registered by build.rs
+include!(concat!(env!("OUT_DIR"), "/registry.rs"));
@@ -0,0 +1,307 @@
+use std::{fmt::Write, fs, path::Path};
+
+const MAX_PIXELS: f64 = 1200.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GRAY: Rgb = Rgb(128, 128, 128);
+    pub const DARK: Rgb = Rgb(40, 40, 48);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(90, 170, 60);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+    pub const BLUE: Rgb = Rgb(40, 110, 200);
+
+    // Spreads neighbouring indices across the hue circle
+    pub fn palette(i: usize) -> Rgb {
+        let hue = (i as f64 * 0.618_033_988_75).fract() * 6.0;
+        let x = 1.0 - (hue % 2.0 - 1.0).abs();
+        let (r, g, b) = match hue as u32 {
+            0 => (1.0, x, 0.0),
+            1 => (x, 1.0, 0.0),
+            2 => (0.0, 1.0, x),
+            3 => (0.0, x, 1.0),
+            4 => (x, 0.0, 1.0),
+            _ => (1.0, 0.0, x),
+        };
+        let channel = |c: f64| (60.0 + c * 180.0) as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+
+    fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: Rgb,
+    },
+    Polygon {
+        points: Vec<(f64, f64)>,
+        fill: Rgb,
+    },
+    Polyline {
+        points: Vec<(f64, f64)>,
+        stroke: Rgb,
+        width: f64,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Drawing {
+    min: (f64, f64),
+    max: (f64, f64),
+    background: Rgb,
+    shapes: Vec<Shape>,
+}
+
+impl Drawing {
+    pub fn new(min: (f64, f64), max: (f64, f64), background: Rgb) -> Self {
+        Drawing {
+            min,
+            max,
+            background,
+            shapes: Vec::new(),
+        }
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: Rgb) {
+        self.shapes.push(Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+        });
+    }
+
+    pub fn polygon(&mut self, points: Vec<(f64, f64)>, fill: Rgb) {
+        self.shapes.push(Shape::Polygon { points, fill });
+    }
+
+    pub fn polyline(&mut self, points: Vec<(f64, f64)>, stroke: Rgb, width: f64) {
+        self.shapes.push(Shape::Polyline {
+            points,
+            stroke,
+            width,
+        });
+    }
+
+    fn size(&self) -> (f64, f64) {
+        (self.max.0 - self.min.0, self.max.1 - self.min.1)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let (w, h) = self.size();
+        let scale = MAX_PIXELS / w.max(h);
+        let points = |points: &[(f64, f64)]| {
+            points
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{:.0}" height="{:.0}">"#,
+            self.min.0,
+            self.min.1,
+            w,
+            h,
+            w * scale,
+            h * scale
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            self.min.0,
+            self.min.1,
+            w,
+            h,
+            self.background.hex()
+        )
+        .unwrap();
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill,
+                } => writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill.hex()
+                ),
+                Shape::Polygon { points: p, fill } => writeln!(
+                    out,
+                    r#"<polygon points="{}" fill="{}"/>"#,
+                    points(p),
+                    fill.hex()
+                ),
+                Shape::Polyline {
+                    points: p,
+                    stroke,
+                    width,
+                } => writeln!(
+                    out,
+                    r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round"/>"#,
+                    points(p),
+                    stroke.hex(),
+                    width
+                ),
+            }
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let (w, h) = self.size();
+        let mut scale = MAX_PIXELS / w.max(h);
+        // Keep grid cells square and evenly sized
+        if scale >= 1.0 {
+            scale = scale.floor();
+        }
+        let width = ((w * scale).ceil() as usize).max(1);
+        let height = ((h * scale).ceil() as usize).max(1);
+        let mut pixels = vec![self.background; width * height];
+
+        let to_pixel = |(x, y): (f64, f64)| ((x - self.min.0) * scale, (y - self.min.1) * scale);
+        let fill_span = |pixels: &mut [Rgb], row: usize, from: f64, to: f64, color: Rgb| {
+            let from = from.round().max(0.0) as usize;
+            let to = (to.round().max(0.0) as usize).min(width);
+            for p in &mut pixels[row * width + from.min(to)..row * width + to] {
+                *p = color;
+            }
+        };
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width: rw,
+                    height: rh,
+                    fill,
+                } => {
+                    let (x0, y0) = to_pixel((*x, *y));
+                    let (x1, y1) = to_pixel((x + rw, y + rh));
+                    let rows = (y0.round().max(0.0) as usize)..(y1.round() as usize).min(height);
+                    for row in rows {
+                        fill_span(&mut pixels, row, x0, x1, *fill);
+                    }
+                }
+                Shape::Polygon { points, fill } => {
+                    let points: Vec<_> = points.iter().map(|&p| to_pixel(p)).collect();
+                    for row in 0..height {
+                        let y = row as f64 + 0.5;
+                        let mut crossings: Vec<f64> = points
+                            .iter()
+                            .zip(points.iter().cycle().skip(1))
+                            .filter(|(a, b)| (a.1 <= y) != (b.1 <= y))
+                            .map(|(a, b)| a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0))
+                            .collect();
+                        crossings.sort_by(|a, b| a.total_cmp(b));
+                        for span in crossings.chunks_exact(2) {
+                            fill_span(&mut pixels, row, span[0], span[1], *fill);
+                        }
+                    }
+                }
+                Shape::Polyline {
+                    points,
+                    stroke,
+                    width: line_width,
+                } => {
+                    let radius = (line_width * scale / 2.0).max(0.5);
+                    for (a, b) in points.iter().zip(points.iter().skip(1)) {
+                        let (a, b) = (to_pixel(*a), to_pixel(*b));
+                        let steps = (b.0 - a.0).abs().max((b.1 - a.1).abs()).ceil().max(1.0);
+                        for i in 0..=steps as usize {
+                            let t = i as f64 / steps;
+                            let (x, y) = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+                            let rows = ((y - radius).round().max(0.0) as usize)
+                                ..((y + radius).round() as usize).min(height);
+                            for row in rows {
+                                fill_span(&mut pixels, row, x - radius, x + radius, *stroke);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        out.extend(pixels.iter().flat_map(|p| [p.0, p.1, p.2]));
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("svg") => self.to_svg().into_bytes(),
+            Some("ppm") => self.to_ppm(),
+            _ => Err(format!(
+                "Unsupported export format for {}, use .svg or .ppm",
+                path.display()
+            ))?,
+        };
+        fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rasterizes_shapes() {
+        let mut drawing = Drawing::new((0.0, 0.0), (4.0, 2.0), Rgb::DARK);
+        drawing.rect(0.0, 0.0, 1.0, 1.0, Rgb::RED);
+        drawing.polygon(
+            vec![(2.0, 0.0), (4.0, 0.0), (4.0, 2.0), (2.0, 2.0)],
+            Rgb::GREEN,
+        );
+
+        let ppm = drawing.to_ppm();
+        let header = "P6\n1200 600\n255\n";
+        assert!(ppm.starts_with(header.as_bytes()));
+
+        let pixel = |x: usize, y: usize| {
+            let i = header.len() + (y * 1200 + x) * 3;
+            Rgb(ppm[i], ppm[i + 1], ppm[i + 2])
+        };
+        assert_eq!(pixel(10, 10), Rgb::RED);
+        assert_eq!(pixel(310, 10), Rgb::DARK);
+        assert_eq!(pixel(310, 310), Rgb::DARK);
+        assert_eq!(pixel(610, 310), Rgb::GREEN);
+        assert_eq!(pixel(1199, 599), Rgb::GREEN);
+    }
+
+    #[test]
+    fn writes_svg() {
+        let mut drawing = Drawing::new((0.0, 0.0), (2.0, 2.0), Rgb::WHITE);
+        drawing.polyline(vec![(0.5, 0.5), (1.5, 0.5)], Rgb::BLUE, 0.2);
+
+        let svg = drawing.to_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"<polyline points="0.5,0.5 1.5,0.5""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path};
+
+#[derive(Debug, Clone, Default)]
+pub struct Node {
+    kind: Option<String>,
+    highlighted: bool,
+}
+
+impl Node {
+    pub fn kind(&mut self, kind: &str) -> &mut Self {
+        self.kind = Some(kind.to_owned());
+        self
+    }
+
+    pub fn highlight(&mut self) -> &mut Self {
+        self.highlighted = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    from: String,
+    to: String,
+    weight: Option<i64>,
+    label: Option<String>,
+    highlighted: bool,
+}
+
+impl Edge {
+    pub fn weight(&mut self, weight: i64) -> &mut Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.label = Some(label.to_owned());
+        self
+    }
+
+    pub fn highlight(&mut self) -> &mut Self {
+        self.highlighted = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    directed: bool,
+    nodes: BTreeMap<String, Node>,
+    edges: Vec<Edge>,
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn attribute_list(attributes: &[String]) -> String {
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Graph {
+    pub fn new(directed: bool) -> Self {
+        Graph {
+            directed,
+            nodes: BTreeMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn node(&mut self, id: &str) -> &mut Node {
+        self.nodes.entry(id.to_owned()).or_default()
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str) -> &mut Edge {
+        self.node(from);
+        self.node(to);
+        self.edges.push(Edge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            weight: None,
+            label: None,
+            highlighted: false,
+        });
+        self.edges.last_mut().unwrap()
+    }
+
+    // Marks the nodes along a walk and the first matching edge for each step
+    pub fn highlight_path<S: AsRef<str>>(&mut self, path: &[S]) {
+        for id in path {
+            self.node(id.as_ref()).highlight();
+        }
+        for step in path.windows(2) {
+            let (from, to) = (step[0].as_ref(), step[1].as_ref());
+            let directed = self.directed;
+            if let Some(edge) = self.edges.iter_mut().find(|e| {
+                (e.from == from && e.to == to) || (!directed && e.from == to && e.to == from)
+            }) {
+                edge.highlighted = true;
+            }
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let (keyword, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        let mut out = format!("{} {{\n", keyword);
+        for (id, node) in &self.nodes {
+            let mut attributes = Vec::new();
+            if let Some(kind) = &node.kind {
+                attributes.push(format!("kind={}", quote(kind)));
+                attributes.push(format!("xlabel={}", quote(kind)));
+            }
+            if node.highlighted {
+                attributes.push("color=red, penwidth=2".to_owned());
+            }
+            writeln!(out, "  {}{};", quote(id), attribute_list(&attributes)).unwrap();
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(weight) = edge.weight {
+                attributes.push(format!("weight={}", weight));
+            }
+            if let Some(label) = edge.label.clone().or(edge.weight.map(|w| w.to_string())) {
+                attributes.push(format!("label={}", quote(&label)));
+            }
+            if edge.highlighted {
+                attributes.push("color=red, penwidth=2".to_owned());
+            }
+            writeln!(
+                out,
+                "  {} {} {}{};",
+                quote(&edge.from),
+                arrow,
+                quote(&edge.to),
+                attribute_list(&attributes)
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"node_highlighted\" for=\"node\" attr.name=\"highlighted\" attr.type=\"boolean\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>\n",
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"edge_highlighted\" for=\"edge\" attr.name=\"highlighted\" attr.type=\"boolean\"/>\n",
+        ));
+        let direction = if self.directed {
+            "directed"
+        } else {
+            "undirected"
+        };
+        writeln!(out, "  <graph id=\"G\" edgedefault=\"{}\">", direction).unwrap();
+
+        for (id, node) in &self.nodes {
+            write!(out, "    <node id=\"{}\">", escape_xml(id)).unwrap();
+            if let Some(kind) = &node.kind {
+                write!(out, "<data key=\"kind\">{}</data>", escape_xml(kind)).unwrap();
+            }
+            if node.highlighted {
+                out.push_str("<data key=\"node_highlighted\">true</data>");
+            }
+            out.push_str("</node>\n");
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            write!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                i,
+                escape_xml(&edge.from),
+                escape_xml(&edge.to)
+            )
+            .unwrap();
+            if let Some(weight) = edge.weight {
+                write!(out, "<data key=\"weight\">{}</data>", weight).unwrap();
+            }
+            if let Some(label) = &edge.label {
+                write!(out, "<data key=\"label\">{}</data>", escape_xml(label)).unwrap();
+            }
+            if edge.highlighted {
+                out.push_str("<data key=\"edge_highlighted\">true</data>");
+            }
+            out.push_str("</edge>\n");
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("dot") | Some("gv") => self.to_dot(),
+            Some("graphml") => self.to_graphml(),
+            _ => Err(format!(
+                "Unsupported graph format for {}, use .dot or .graphml",
+                path.display()
+            ))?,
+        };
+        fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Graph {
+        let mut graph = Graph::new(false);
+        graph.node("a").kind("flip-flop");
+        graph.edge("a", "b").weight(3);
+        graph.edge("c", "b").label("x<5");
+        graph.edge("c", "d");
+        graph.highlight_path(&["a", "b", "c"]);
+        graph
+    }
+
+    #[test]
+    fn writes_dot() {
+        assert_eq!(
+            sample().to_dot(),
+            concat!(
+                "graph {\n",
+                "  \"a\" [kind=\"flip-flop\", xlabel=\"flip-flop\", color=red, penwidth=2];\n",
+                "  \"b\" [color=red, penwidth=2];\n",
+                "  \"c\" [color=red, penwidth=2];\n",
+                "  \"d\";\n",
+                "  \"a\" -- \"b\" [weight=3, label=\"3\", color=red, penwidth=2];\n",
+                "  \"c\" -- \"b\" [label=\"x<5\", color=red, penwidth=2];\n",
+                "  \"c\" -- \"d\";\n",
+                "}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn writes_graphml() {
+        let graphml = sample().to_graphml();
+        assert!(graphml.contains("<graph id=\"G\" edgedefault=\"undirected\">"));
+        assert!(graphml.contains(
+            "<node id=\"a\"><data key=\"kind\">flip-flop</data><data key=\"node_highlighted\">true</data></node>"
+        ));
+        assert!(graphml.contains(
+            "<edge id=\"e1\" source=\"c\" target=\"b\"><data key=\"label\">x&lt;5</data><data key=\"edge_highlighted\">true</data></edge>"
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn from_bounds(start: i64, end_excl: i64) -> Self {
+        Interval {
+            start,
+            end: end_excl,
+        }
+    }
+
+    pub fn from_length(start: i64, length: i64) -> Self {
+        Interval {
+            start,
+            end: start + length,
+        }
+    }
+
+    pub fn len(&self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn shift(&self, offset: i64) -> Self {
+        Interval {
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let i = Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        };
+        (!i.is_empty()).then_some(i)
+    }
+
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if self.intersect(other).is_none() {
+            return vec![*self];
+        }
+        let below = Interval::from_bounds(self.start, other.start);
+        let above = Interval::from_bounds(other.end, self.end);
+        [below, above]
+            .into_iter()
+            .filter(|i| !i.is_empty())
+            .collect()
+    }
+
+    pub fn split_at(&self, at: i64) -> (Option<Interval>, Option<Interval>) {
+        let below = Interval::from_bounds(self.start, at.min(self.end));
+        let above = Interval::from_bounds(at.max(self.start), self.end);
+        (
+            (!below.is_empty()).then_some(below),
+            (!above.is_empty()).then_some(above),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet(Vec<Interval>);
+
+impl RangeSet {
+    pub fn new() -> Self {
+        RangeSet(Vec::new())
+    }
+
+    fn normalize(mut intervals: Vec<Interval>) -> Self {
+        intervals.retain(|i| !i.is_empty());
+        intervals.sort();
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for i in intervals {
+            match merged.last_mut() {
+                Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+                _ => merged.push(i),
+            }
+        }
+        RangeSet(merged)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval> {
+        self.0.iter()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.0.first().map(|i| i.start)
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        RangeSet::normalize(self.0.iter().chain(other.0.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        RangeSet::normalize(
+            self.0
+                .iter()
+                .flat_map(|a| other.0.iter().filter_map(|b| a.intersect(b)))
+                .collect(),
+        )
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut remaining = self.0.clone();
+        for b in &other.0 {
+            remaining = remaining.iter().flat_map(|a| a.difference(b)).collect();
+        }
+        RangeSet::normalize(remaining)
+    }
+}
+
+impl From<Interval> for RangeSet {
+    fn from(value: Interval) -> Self {
+        RangeSet::normalize(vec![value])
+    }
+}
+
+impl FromIterator<Interval> for RangeSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        RangeSet::normalize(iter.into_iter().collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyperRect<const N: usize>(pub [Interval; N]);
+
+impl<const N: usize> HyperRect<N> {
+    pub fn volume(&self) -> i64 {
+        self.0.iter().map(|i| i.len()).product()
+    }
+
+    pub fn split(&self, axis: usize, at: i64) -> (Option<Self>, Option<Self>) {
+        let (below, above) = self.0[axis].split_at(at);
+        let with_axis = |i: Interval| {
+            let mut bounds = self.0;
+            bounds[axis] = i;
+            HyperRect(bounds)
+        };
+        (below.map(with_axis), above.map(with_axis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_set_merges() {
+        let a: RangeSet = [
+            Interval::from_bounds(8, 12),
+            Interval::from_bounds(0, 5),
+            Interval::from_bounds(5, 7),
+            Interval::from_bounds(9, 9),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            a.iter().copied().collect::<Vec<_>>(),
+            vec![Interval::from_bounds(0, 7), Interval::from_bounds(8, 12)]
+        );
+        assert_eq!(a.min(), Some(0));
+        assert_eq!(RangeSet::from(Interval::from_bounds(3, 3)).min(), None);
+    }
+
+    #[test]
+    fn range_set_union() {
+        let a: RangeSet = [Interval::from_bounds(0, 5), Interval::from_bounds(10, 15)]
+            .into_iter()
+            .collect();
+        let b: RangeSet = [Interval::from_bounds(3, 8), Interval::from_bounds(15, 20)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            a.union(&b),
+            [Interval::from_bounds(0, 8), Interval::from_bounds(10, 20)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(a.union(&RangeSet::new()), a);
+    }
+
+    #[test]
+    fn range_set_intersection() {
+        let a = RangeSet::from(Interval::from_bounds(0, 10));
+        let b: RangeSet = [Interval::from_bounds(3, 4), Interval::from_bounds(8, 12)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            a.intersection(&b),
+            [Interval::from_bounds(3, 4), Interval::from_bounds(8, 10)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            a.intersection(&RangeSet::from(Interval::from_bounds(10, 20))),
+            RangeSet::new()
+        );
+    }
+
+    #[test]
+    fn range_set_difference() {
+        let a = RangeSet::from(Interval::from_bounds(0, 10));
+        let b: RangeSet = [Interval::from_bounds(3, 4), Interval::from_bounds(8, 12)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            a.difference(&b),
+            [Interval::from_bounds(0, 3), Interval::from_bounds(4, 8)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(b.difference(&b), RangeSet::new());
+        assert_eq!(a.difference(&RangeSet::new()), a);
+    }
+
+    #[test]
+    fn hyper_rect_split() {
+        let rect = HyperRect([Interval::from_bounds(1, 11), Interval::from_bounds(1, 5)]);
+
+        let (below, above) = rect.split(0, 4);
+        assert_eq!(below.unwrap().volume(), 12);
+        assert_eq!(above.unwrap().volume(), 28);
+
+        let (below, above) = rect.split(1, 100);
+        assert_eq!(below, Some(rect));
+        assert!(above.is_none());
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Default)]
+struct State {
+    next: HashMap<u8, usize>,
+    fail: usize,
+    // Indices of every pattern ending here, including those reached via fail links
+    outputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'a, T> {
+    pub start: usize,
+    pub end: usize,
+    pub pattern: &'a str,
+    pub value: &'a T,
+}
+
+// Aho-Corasick automaton reporting every match, overlapping ones included
+#[derive(Debug)]
+pub struct Matcher<T> {
+    patterns: Vec<(String, T)>,
+    states: Vec<State>,
+}
+
+impl<T> Matcher<T> {
+    pub fn new(patterns: Vec<(String, T)>) -> Self {
+        let mut states = vec![State::default()];
+        for (i, (pattern, _)) in patterns.iter().enumerate() {
+            let mut current = 0;
+            for &b in pattern.as_bytes() {
+                current = match states[current].next.get(&b) {
+                    Some(&s) => s,
+                    None => {
+                        states.push(State::default());
+                        let s = states.len() - 1;
+                        states[current].next.insert(b, s);
+                        s
+                    }
+                };
+            }
+            states[current].outputs.push(i);
+        }
+
+        let mut queue: VecDeque<_> = states[0].next.values().copied().collect();
+        while let Some(s) = queue.pop_front() {
+            let edges: Vec<_> = states[s].next.iter().map(|(&b, &t)| (b, t)).collect();
+            for (b, t) in edges {
+                let mut fail = states[s].fail;
+                while fail != 0 && !states[fail].next.contains_key(&b) {
+                    fail = states[fail].fail;
+                }
+                let fail = states[fail].next.get(&b).copied().unwrap_or(0);
+                states[t].fail = fail;
+                let inherited = states[fail].outputs.clone();
+                states[t].outputs.extend(inherited);
+                queue.push_back(t);
+            }
+        }
+
+        Matcher { patterns, states }
+    }
+
+    pub fn find_all<'a>(&'a self, haystack: &str) -> Vec<Match<'a, T>> {
+        let mut matches = Vec::new();
+        let mut current = 0;
+        for (i, &b) in haystack.as_bytes().iter().enumerate() {
+            while current != 0 && !self.states[current].next.contains_key(&b) {
+                current = self.states[current].fail;
+            }
+            current = self.states[current].next.get(&b).copied().unwrap_or(0);
+            for &p in &self.states[current].outputs {
+                let (pattern, value) = &self.patterns[p];
+                let start = i + 1 - pattern.len();
+                matches.push((
+                    (start, p),
+                    Match {
+                        start,
+                        end: i + 1,
+                        pattern,
+                        value,
+                    },
+                ));
+            }
+        }
+        // Matches starting together keep the order their patterns were given in
+        matches.sort_by_key(|(key, _)| *key);
+        matches.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_overlapping_matches() {
+        let matcher = Matcher::new(
+            ["one", "two", "twone", "ne", "1"]
+                .iter()
+                .map(|p| (p.to_string(), p.len()))
+                .collect(),
+        );
+
+        let found: Vec<_> = matcher
+            .find_all("xtwone1")
+            .iter()
+            .map(|m| (m.start, m.pattern))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(1, "two"), (1, "twone"), (3, "one"), (4, "ne"), (6, "1")]
+        );
+        assert!(matcher.find_all("tw").is_empty());
+    }
+
+    #[test]
+    fn ties_follow_pattern_order() {
+        let matcher = Matcher::new(vec![("twone".to_owned(), 0), ("two".to_owned(), 1)]);
+
+        let found: Vec<_> = matcher
+            .find_all("twone")
+            .iter()
+            .map(|m| m.pattern)
+            .collect();
+        assert_eq!(found, vec!["twone", "two"]);
+    }
+}
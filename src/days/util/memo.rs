@@ -0,0 +1,102 @@
+use std::{collections::HashMap, fmt::Display, hash::Hash, ops::Add};
+
+type Recurse<'a, K, V> = dyn Fn(&mut Memo<'a, K, V>, K) -> V + 'a;
+
+pub struct Memo<'a, K, V> {
+    f: &'a Recurse<'a, K, V>,
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<'a, K: Hash + Eq + Clone, V: Clone> Memo<'a, K, V> {
+    pub fn new(f: &'a Recurse<'a, K, V>) -> Self {
+        Memo {
+            f,
+            cache: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        if let Some(v) = self.cache.get(&key) {
+            self.hits += 1;
+            return v.clone();
+        }
+        self.misses += 1;
+
+        let f = self.f;
+        let v = f(self, key.clone());
+        self.cache.insert(key, v.clone());
+        v
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+impl Add for MemoStats {
+    type Output = MemoStats;
+
+    fn add(self, other: MemoStats) -> MemoStats {
+        MemoStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+        }
+    }
+}
+
+impl Display for MemoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fibonacci_hits_cache() {
+        let mut memo = Memo::new(&|memo: &mut Memo<u64, u64>, n| {
+            if n < 2 {
+                n
+            } else {
+                memo.get(n - 1) + memo.get(n - 2)
+            }
+        });
+
+        assert_eq!(memo.get(90), 2880067194370816120);
+        assert_eq!(
+            memo.stats(),
+            MemoStats {
+                hits: 88,
+                misses: 91
+            }
+        );
+    }
+
+    #[test]
+    fn borrowed_keys() {
+        let words = ["a", "bb", "ccc"];
+        let mut memo = Memo::new(&|memo: &mut Memo<&[&str], usize>, rest| match rest {
+            [] => 0,
+            [first, tail @ ..] => first.len() + memo.get(tail),
+        });
+
+        assert_eq!(memo.get(&words), 6);
+        assert_eq!(memo.get(&words[1..]), 5);
+        assert_eq!(memo.stats().hits, 1);
+    }
+}
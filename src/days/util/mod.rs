@@ -0,0 +1,75 @@
+pub mod export;
+pub mod graph;
+pub mod interval;
+pub mod matcher;
+pub mod memo;
+pub mod parse;
+pub mod poly;
+pub mod polygon;
+pub mod profile;
+pub mod ratio;
+pub mod render;
+
+use std::mem::swap;
+
+fn gcd(a: usize, b: usize) -> usize {
+    let mut r = (a, b);
+
+    while r.0 != 0 {
+        let q = r.1 / r.0;
+        swap(&mut r.0, &mut r.1);
+        r.0 -= q * r.1;
+    }
+
+    r.1
+}
+
+pub fn lcm(a: usize, b: usize) -> usize {
+    a * b / gcd(a, b)
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+// Combines x = a mod m and x = b mod n, None if they never agree
+pub fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Result<Option<(i128, i128)>, String> {
+    let overflow = || format!("Overflow combining {} mod {} with {} mod {}", a, m, b, n);
+    let (g, p, _) = extended_gcd(m, n);
+    let difference = b.checked_sub(a).ok_or_else(overflow)?;
+    if difference % g != 0 {
+        return Ok(None);
+    }
+
+    let l = (m / g).checked_mul(n).ok_or_else(overflow)?;
+    let k = (difference / g % (n / g))
+        .checked_mul(p % (n / g))
+        .ok_or_else(overflow)?
+        % (n / g);
+    let x = m
+        .checked_mul(k)
+        .and_then(|mk| a.checked_add(mk))
+        .ok_or_else(overflow)?
+        % l;
+    Ok(Some(((x.checked_add(l).ok_or_else(overflow)?) % l, l)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crt_tells_disagreement_from_overflow() {
+        assert_eq!(crt((2, 3), (3, 5)), Ok(Some((8, 15))));
+        assert_eq!(crt((1, 4), (2, 6)), Ok(None));
+        assert!(crt((0, 1 << 70), (1, 3i128.pow(50))).is_err());
+        // Only the step from a towards b overflows
+        assert!(crt((i128::MAX, 2), (0, 3)).is_err());
+        assert!(crt((i128::MIN, 3), (i128::MAX, 5)).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(column: usize, message: String) -> Self {
+        ParseError {
+            line: 1,
+            column,
+            message,
+        }
+    }
+
+    pub fn on_line(self, line: usize) -> Self {
+        ParseError { line, ..self }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl From<ParseError> for String {
+    fn from(value: ParseError) -> Self {
+        value.to_string()
+    }
+}
+
+fn parse_at<T: FromStr>(s: &str, column: usize) -> Result<T, ParseError>
+where
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| ParseError::new(column, format!("{} in {:?}", e, s)))
+}
+
+pub fn integers<T: FromStr>(line: &str) -> Result<Vec<T>, ParseError>
+where
+    T::Err: Display,
+{
+    let bytes = line.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if bytes[i].is_ascii_digit() || negative {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            found.push(parse_at(&line[start..i], start + 1)?);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(found)
+}
+
+pub fn sections<S: AsRef<str>>(lines: &[S]) -> Vec<&[S]> {
+    lines
+        .split(|l| l.as_ref().is_empty())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+pub fn key_value<'a>(line: &'a str, separator: &str) -> Result<(&'a str, &'a str), ParseError> {
+    line.split_once(separator)
+        .ok_or_else(|| ParseError::new(1, format!("missing {:?} in {:?}", separator, line)))
+}
+
+pub fn key_values<'a, S: AsRef<str>>(
+    lines: &'a [S],
+    separator: &str,
+) -> Result<Vec<(&'a str, &'a str)>, ParseError> {
+    lines
+        .iter()
+        .zip(1..)
+        .map(|(l, n)| key_value(l.as_ref(), separator).map_err(|e| e.on_line(n)))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct LinePattern(Vec<String>);
+
+#[derive(Debug)]
+pub struct Captures<'a>(Vec<(usize, &'a str)>);
+
+impl LinePattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let literals: Vec<_> = pattern.split("{}").map(|s| s.to_owned()).collect();
+        if literals.len() < 2 {
+            return Err(format!("Pattern has no fields: {:?}", pattern));
+        }
+        if literals[1..literals.len() - 1].iter().any(|l| l.is_empty()) {
+            return Err(format!("Adjacent fields are ambiguous: {:?}", pattern));
+        }
+        Ok(LinePattern(literals))
+    }
+
+    pub fn captures<'a>(&self, line: &'a str) -> Result<Captures<'a>, ParseError> {
+        let mismatch = |column: usize, literal: &str| {
+            ParseError::new(column, format!("expected {:?} in {:?}", literal, line))
+        };
+
+        let first = &self.0[0];
+        if !line.starts_with(first.as_str()) {
+            return Err(mismatch(1, first));
+        }
+        let mut pos = first.len();
+
+        let last = self.0.len() - 1;
+        let mut fields = Vec::new();
+        for (i, literal) in self.0.iter().enumerate().skip(1) {
+            let end = if i == last {
+                if !line[pos..].ends_with(literal.as_str()) {
+                    return Err(mismatch(
+                        line.len().saturating_sub(literal.len()) + 1,
+                        literal,
+                    ));
+                }
+                line.len() - literal.len()
+            } else {
+                pos + line[pos..]
+                    .find(literal.as_str())
+                    .ok_or_else(|| mismatch(pos + 1, literal))?
+            };
+            fields.push((pos + 1, &line[pos..end]));
+            pos = end + literal.len();
+        }
+
+        Ok(Captures(fields))
+    }
+}
+
+impl<'a> Captures<'a> {
+    pub fn str(&self, i: usize) -> &'a str {
+        self.0[i].1
+    }
+
+    pub fn get<T: FromStr>(&self, i: usize) -> Result<T, ParseError>
+    where
+        T::Err: Display,
+    {
+        let (column, s) = self.0[i];
+        let trimmed = s.trim_start();
+        parse_at(trimmed.trim_end(), column + s.len() - trimmed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_signed_integers() {
+        assert_eq!(
+            integers::<i64>("x=-12, y=7 -> 3-4").unwrap(),
+            vec![-12, 7, 3, -4]
+        );
+
+        let error = integers::<u8>("Card 1: 300").unwrap_err().on_line(4);
+        assert_eq!((error.line, error.column), (4, 9));
+    }
+
+    #[test]
+    fn splits_sections() {
+        let lines = ["a", "b", "", "", "c", ""];
+        assert_eq!(sections(&lines), vec![&lines[0..2], &lines[4..5]]);
+    }
+
+    #[test]
+    fn matches_patterns() {
+        let pattern = LinePattern::new("{} = ({}, {})").unwrap();
+        let captures = pattern.captures("AAA = (BBB, CCC)").unwrap();
+        assert_eq!(captures.str(0), "AAA");
+        assert_eq!(captures.str(2), "CCC");
+
+        let error = pattern.captures("AAA = (BBB CCC)").unwrap_err();
+        assert_eq!(error.column, 8);
+
+        let block = LinePattern::new("{},{},{}~{},{},{}").unwrap();
+        let captures = block.captures("1,0,1~1,x,1").unwrap();
+        assert_eq!(captures.get::<u16>(0).unwrap(), 1);
+        assert_eq!(captures.get::<u16>(4).unwrap_err().column, 9);
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+        assert!(LinePattern::new("no fields").is_err());
+        assert!(LinePattern::new("{}{}").is_err());
+
+        let pattern = LinePattern::new("{} is a long suffix").unwrap();
+        assert_eq!(pattern.captures("x").unwrap_err().column, 1);
+    }
+}
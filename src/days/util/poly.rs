@@ -0,0 +1,181 @@
+pub fn differences(values: &[i64]) -> Result<Vec<i64>, String> {
+    values
+        .windows(2)
+        .map(|w| {
+            w[1].checked_sub(w[0])
+                .ok_or(format!("Overflow taking difference {} - {}", w[1], w[0]))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    origin: i64,
+    step: i64,
+    leading: Vec<i64>,
+}
+
+impl Polynomial {
+    pub fn fit(samples: &[i64], degree: usize) -> Result<Self, String> {
+        if samples.len() <= degree {
+            return Err(format!(
+                "Need {} samples for degree {}, got {}",
+                degree + 1,
+                degree,
+                samples.len()
+            ));
+        }
+
+        let mut leading = Vec::new();
+        let mut row = samples.to_vec();
+        for _ in 0..=degree {
+            leading.push(row[0]);
+            row = differences(&row)?;
+        }
+
+        if row.iter().any(|&d| d != 0) {
+            return Err(format!(
+                "Samples do not fit a polynomial of degree {}: {:?}",
+                degree, samples
+            ));
+        }
+
+        Ok(Polynomial {
+            origin: 0,
+            step: 1,
+            leading,
+        })
+    }
+
+    pub fn settle(samples: &[i64]) -> Result<Self, String> {
+        if samples.is_empty() {
+            return Err("Cannot extrapolate an empty history".to_owned());
+        }
+
+        // The zero row must still hold a value, otherwise nothing shows the history settled
+        let mut leading = Vec::new();
+        let mut row = samples.to_vec();
+        while row.iter().any(|&d| d != 0) {
+            if row.len() == 1 {
+                return Err(format!("History never settles to zero: {:?}", samples));
+            }
+            leading.push(row[0]);
+            row = differences(&row)?;
+        }
+
+        if leading.is_empty() {
+            leading.push(0);
+        }
+
+        Ok(Polynomial {
+            origin: 0,
+            step: 1,
+            leading,
+        })
+    }
+
+    pub fn spaced(self, origin: i64, step: i64) -> Result<Self, String> {
+        if step == 0 {
+            return Err("Sample spacing must be non-zero".to_owned());
+        }
+        Ok(Polynomial {
+            origin,
+            step,
+            ..self
+        })
+    }
+
+    pub fn at_index(&self, t: i64) -> Result<i64, String> {
+        let overflow = || format!("Overflow evaluating polynomial at index {}", t);
+
+        let mut total: i128 = 0;
+        let mut binomial: i128 = 1;
+        for (j, &d) in self.leading.iter().enumerate() {
+            total = binomial
+                .checked_mul(d as i128)
+                .and_then(|v| v.checked_add(total))
+                .ok_or_else(overflow)?;
+            if j + 1 == self.leading.len() {
+                break;
+            }
+            binomial = binomial
+                .checked_mul(t as i128 - j as i128)
+                .ok_or_else(overflow)?
+                / (j as i128 + 1);
+        }
+
+        i64::try_from(total).map_err(|_| overflow())
+    }
+
+    pub fn at(&self, x: i64) -> Result<i64, String> {
+        let offset = x
+            .checked_sub(self.origin)
+            .ok_or(format!("Overflow evaluating polynomial at {}", x))?;
+        if offset % self.step != 0 {
+            return Err(format!(
+                "{} is not on the sample grid {} + {}n",
+                x, self.origin, self.step
+            ));
+        }
+        self.at_index(offset / self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolates_both_directions() {
+        let poly = Polynomial::settle(&[10, 13, 16, 21, 30, 45]).unwrap();
+
+        assert_eq!(poly.leading.len(), 4);
+        assert_eq!(poly.at_index(6).unwrap(), 68);
+        assert_eq!(poly.at_index(-1).unwrap(), 5);
+    }
+
+    #[test]
+    fn fits_spaced_quadratic() {
+        let samples: Vec<_> = [3, 10, 17].iter().map(|x| 2 * x * x - x + 4).collect();
+        let poly = Polynomial::fit(&samples, 2).unwrap().spaced(3, 7).unwrap();
+
+        assert_eq!(poly.at(101).unwrap(), 2 * 101 * 101 - 101 + 4);
+        assert_eq!(poly.at(-4).unwrap(), 2 * 16 + 4 + 4);
+        assert!(poly.at(5).is_err());
+        assert!(Polynomial::fit(&samples, 2).unwrap().spaced(3, 0).is_err());
+    }
+
+    #[test]
+    fn evaluates_far_indices() {
+        // C(t, 3) would overflow, but no term needs it
+        let poly = Polynomial::fit(&[5, 5, 5], 2).unwrap();
+        assert_eq!(poly.at_index(i64::MAX).unwrap(), 5);
+
+        let poly = poly.spaced(1, 1).unwrap();
+        assert!(poly.at(i64::MIN).is_err());
+    }
+
+    #[test]
+    fn settles_short_histories() {
+        let poly = Polynomial::settle(&[5, 5]).unwrap();
+        assert_eq!(poly.at_index(2).unwrap(), 5);
+        assert_eq!(poly.at_index(-1).unwrap(), 5);
+
+        let poly = Polynomial::settle(&[0]).unwrap();
+        assert_eq!(poly.at_index(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn reports_unsettled() {
+        assert!(Polynomial::settle(&[5]).is_err());
+        assert!(Polynomial::settle(&[1, 2, 4, 8, 16]).is_err());
+        assert!(Polynomial::settle(&[]).is_err());
+        assert!(Polynomial::settle(&[i64::MIN, i64::MAX]).is_err());
+    }
+
+    #[test]
+    fn reports_unfitted() {
+        assert!(Polynomial::fit(&[1, 2, 4, 8, 16], 2).is_err());
+        assert!(Polynomial::fit(&[1, 2], 2).is_err());
+    }
+}
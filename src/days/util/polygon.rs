@@ -0,0 +1,105 @@
+use super::{gcd, ratio::Ratio};
+
+#[derive(Debug, Clone)]
+pub struct Polygon(Vec<(i64, i64)>);
+
+impl Polygon {
+    pub fn new(vertices: Vec<(i64, i64)>) -> Self {
+        Polygon(vertices)
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&(i64, i64), &(i64, i64))> {
+        self.0.iter().zip(self.0.iter().cycle().skip(1))
+    }
+
+    pub fn double_area(&self) -> Result<i128, String> {
+        self.edges()
+            .try_fold(0i128, |sum, (a, b)| {
+                let term = (a.0 as i128 * b.1 as i128).checked_sub(b.0 as i128 * a.1 as i128)?;
+                sum.checked_add(term)
+            })
+            .and_then(i128::checked_abs)
+            .ok_or("Overflow taking the shoelace sum".to_owned())
+    }
+
+    pub fn area(&self) -> Result<Ratio, String> {
+        Ok(Ratio::new(self.double_area()?, 2).expect("a non-zero denominator"))
+    }
+
+    pub fn boundary_points(&self) -> Result<i64, String> {
+        self.edges()
+            .try_fold(0i64, |sum, (a, b)| {
+                let dx = b.0.checked_sub(a.0)?.unsigned_abs() as usize;
+                let dy = b.1.checked_sub(a.1)?.unsigned_abs() as usize;
+                sum.checked_add(i64::try_from(gcd(dx, dy)).ok()?)
+            })
+            .ok_or("Overflow counting boundary points".to_owned())
+    }
+
+    // Pick's theorem only gives a whole number for a simple polygon on the lattice
+    pub fn interior_points(&self) -> Result<i64, String> {
+        let half_boundary =
+            Ratio::new(self.boundary_points()? as i128, 2).expect("half an i64 fits in a Ratio");
+        let interior = self
+            .area()?
+            .checked_sub(half_boundary)
+            .and_then(|r| r.checked_add(Ratio::from(1)))
+            .ok_or("Overflow applying Pick's theorem")?;
+        interior
+            .to_integer()
+            .ok_or(format!("Polygon has {} interior points", interior))
+            .and_then(|n| {
+                i64::try_from(n).map_err(|_| "Overflow counting interior points".to_owned())
+            })
+    }
+
+    pub fn lattice_points(&self) -> Result<i64, String> {
+        self.interior_points()?
+            .checked_add(self.boundary_points()?)
+            .ok_or("Overflow counting lattice points".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square() {
+        let square = Polygon::new(vec![(0, 0), (4, 0), (4, 4), (0, 4)]);
+
+        assert_eq!(square.area().unwrap(), Ratio::from(16));
+        assert_eq!(square.boundary_points(), Ok(16));
+        assert_eq!(square.interior_points().unwrap(), 9);
+        assert_eq!(square.lattice_points().unwrap(), 25);
+    }
+
+    #[test]
+    fn triangle_with_repeated_closing_vertex() {
+        let triangle = Polygon::new(vec![(0, 0), (3, 0), (0, 3), (0, 0)]);
+
+        assert_eq!(triangle.area().unwrap(), Ratio::new(9, 2).unwrap());
+        assert_eq!(triangle.boundary_points(), Ok(9));
+        assert_eq!(triangle.interior_points().unwrap(), 1);
+    }
+
+    #[test]
+    fn wide_area() {
+        let wide = Polygon::new(vec![
+            (0, 0),
+            (i64::MAX, 0),
+            (i64::MAX, i64::MAX),
+            (0, i64::MAX),
+        ]);
+        assert_eq!(wide.double_area().unwrap(), 2 * (i64::MAX as i128).pow(2));
+
+        let extreme = Polygon::new(vec![
+            (i64::MIN, i64::MIN),
+            (i64::MAX, i64::MIN),
+            (i64::MIN, i64::MAX),
+        ]);
+        assert!(extreme.double_area().is_err());
+        assert!(extreme.boundary_points().is_err());
+        assert!(extreme.interior_points().is_err());
+    }
+}
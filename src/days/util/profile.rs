@@ -0,0 +1,285 @@
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Folded,
+    Chrome,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Format::Chrome,
+            _ => Format::Folded,
+        }
+    }
+}
+
+#[cfg(not(feature = "profile"))]
+mod recorder {
+    use std::path::Path;
+
+    use super::Format;
+
+    pub struct Span;
+
+    #[derive(Clone)]
+    pub struct Parent;
+
+    #[inline(always)]
+    pub fn span(_name: &'static str) -> Span {
+        Span
+    }
+
+    #[inline(always)]
+    pub fn parent() -> Parent {
+        Parent
+    }
+
+    #[inline(always)]
+    pub fn span_in(_parent: &Parent, _name: &'static str) -> Span {
+        Span
+    }
+
+    pub fn enable(_format: Format) -> Result<(), String> {
+        Err("Built without the profile feature, rerun with --features profile".to_owned())
+    }
+
+    pub fn write(_path: &Path) -> Result<(), String> {
+        enable(Format::Folded)
+    }
+}
+
+#[cfg(feature = "profile")]
+mod recorder {
+    use std::{
+        cell::RefCell,
+        collections::HashMap,
+        fs,
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex, OnceLock,
+        },
+        time::Instant,
+    };
+
+    use super::Format;
+
+    struct Event {
+        name: &'static str,
+        tid: usize,
+        start_ns: u64,
+        duration_ns: u64,
+    }
+
+    #[derive(Default)]
+    struct Buffer {
+        folded: HashMap<Vec<&'static str>, u64>,
+        events: Vec<Event>,
+    }
+
+    struct Frame {
+        // Names from the root span down to this one, which may have started on another thread
+        path: Vec<&'static str>,
+        start: Instant,
+        child_ns: u64,
+    }
+
+    struct Local {
+        tid: usize,
+        stack: Vec<Frame>,
+        buffer: Arc<Mutex<Buffer>>,
+    }
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+    static CHROME: AtomicBool = AtomicBool::new(false);
+    static NEXT_TID: AtomicUsize = AtomicUsize::new(1);
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    static BUFFERS: Mutex<Vec<Arc<Mutex<Buffer>>>> = Mutex::new(Vec::new());
+
+    thread_local! {
+        static LOCAL: RefCell<Option<Local>> = const { RefCell::new(None) };
+    }
+
+    fn with_local<R>(f: impl FnOnce(&mut Local) -> R) -> R {
+        LOCAL.with(|l| {
+            let mut l = l.borrow_mut();
+            let local = l.get_or_insert_with(|| {
+                let buffer = Arc::new(Mutex::new(Buffer::default()));
+                BUFFERS.lock().unwrap().push(buffer.clone());
+                Local {
+                    tid: NEXT_TID.fetch_add(1, Ordering::Relaxed),
+                    stack: Vec::new(),
+                    buffer,
+                }
+            });
+            f(local)
+        })
+    }
+
+    pub struct Span {
+        active: bool,
+    }
+
+    // The enclosing span of a thread, to hand over to work it spawns on other threads
+    #[derive(Clone)]
+    pub struct Parent(Vec<&'static str>);
+
+    fn push(name: &'static str, parent: Option<&Parent>) -> Span {
+        let active = ENABLED.load(Ordering::Relaxed);
+        if active {
+            with_local(|local| {
+                let mut path = match parent {
+                    Some(Parent(path)) => path.clone(),
+                    None => local.stack.last().map_or(Vec::new(), |f| f.path.clone()),
+                };
+                path.push(name);
+                local.stack.push(Frame {
+                    path,
+                    start: Instant::now(),
+                    child_ns: 0,
+                })
+            });
+        }
+        Span { active }
+    }
+
+    pub fn span(name: &'static str) -> Span {
+        push(name, None)
+    }
+
+    pub fn parent() -> Parent {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return Parent(Vec::new());
+        }
+        with_local(|local| Parent(local.stack.last().map_or(Vec::new(), |f| f.path.clone())))
+    }
+
+    // Spans on worker threads start from an empty stack, so they need the spawning span passed in
+    pub fn span_in(parent: &Parent, name: &'static str) -> Span {
+        push(name, Some(parent))
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            if !self.active {
+                return;
+            }
+
+            with_local(|local| {
+                let frame = local.stack.pop().unwrap();
+                let duration_ns = frame.start.elapsed().as_nanos() as u64;
+                if let Some(parent) = local.stack.last_mut() {
+                    parent.child_ns += duration_ns;
+                }
+
+                let mut buffer = local.buffer.lock().unwrap();
+                let name = *frame.path.last().unwrap();
+                *buffer.folded.entry(frame.path).or_default() += duration_ns - frame.child_ns;
+                if CHROME.load(Ordering::Relaxed) {
+                    let epoch = EPOCH.get_or_init(Instant::now);
+                    buffer.events.push(Event {
+                        name,
+                        tid: local.tid,
+                        start_ns: frame.start.duration_since(*epoch).as_nanos() as u64,
+                        duration_ns,
+                    });
+                }
+            })
+        }
+    }
+
+    pub fn enable(format: Format) -> Result<(), String> {
+        EPOCH.get_or_init(Instant::now);
+        CHROME.store(format == Format::Chrome, Ordering::Relaxed);
+        ENABLED.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn folded(buffers: &[Arc<Mutex<Buffer>>]) -> String {
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        for buffer in buffers {
+            for (path, ns) in &buffer.lock().unwrap().folded {
+                *totals.entry(path.join(";")).or_default() += ns;
+            }
+        }
+
+        let mut lines: Vec<_> = totals
+            .into_iter()
+            .map(|(path, ns)| format!("{} {}\n", path, ns / 1000))
+            .collect();
+        lines.sort();
+        lines.concat()
+    }
+
+    fn chrome(buffers: &[Arc<Mutex<Buffer>>]) -> String {
+        let mut events = Vec::new();
+        for buffer in buffers {
+            for e in &buffer.lock().unwrap().events {
+                events.push(format!(
+                    "{{\"name\":{:?},\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}",
+                    e.name,
+                    e.tid,
+                    e.start_ns as f64 / 1000.0,
+                    e.duration_ns as f64 / 1000.0
+                ));
+            }
+        }
+        format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"))
+    }
+
+    pub fn write(path: &Path) -> Result<(), String> {
+        ENABLED.store(false, Ordering::Relaxed);
+        let buffers = BUFFERS.lock().unwrap();
+        let contents = match Format::from_path(path) {
+            Format::Folded => folded(&buffers),
+            Format::Chrome => chrome(&buffers),
+        };
+        fs::write(path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+
+        use super::*;
+
+        #[test]
+        fn folds_nested_spans() {
+            enable(Format::Folded).unwrap();
+            {
+                let _outer = span("test outer");
+                {
+                    let _inner = span("test inner");
+                }
+                let parent = parent();
+                thread::spawn(move || {
+                    let _worker = span_in(&parent, "test worker");
+                    let _nested = span("test nested");
+                })
+                .join()
+                .unwrap();
+            }
+
+            let folded = folded(&BUFFERS.lock().unwrap());
+            let paths: Vec<_> = folded
+                .lines()
+                .filter_map(|l| l.rsplit_once(' '))
+                .map(|(path, _)| path)
+                .filter(|path| path.starts_with("test "))
+                .collect();
+            assert_eq!(
+                paths,
+                vec![
+                    "test outer",
+                    "test outer;test inner",
+                    "test outer;test worker",
+                    "test outer;test worker;test nested"
+                ]
+            );
+        }
+    }
+}
+
+pub use recorder::{enable, parent, span, span_in, write};
@@ -0,0 +1,208 @@
+use std::{cmp::Ordering, fmt::Display};
+
+fn gcd(a: u128, b: u128) -> u128 {
+    let mut r = (a, b);
+    while r.1 != 0 {
+        r = (r.1, r.0 % r.1);
+    }
+    r.0
+}
+
+// Compares a / da with b / db for 0 <= a < da and 0 <= b < db by expanding both as continued
+// fractions, so nothing is ever multiplied
+fn cmp_proper(mut a: i128, mut da: i128, mut b: i128, mut db: i128) -> Ordering {
+    let mut flipped = false;
+    loop {
+        let ordering = match (a, b) {
+            (0, 0) => Some(Ordering::Equal),
+            (0, _) => Some(Ordering::Less),
+            (_, 0) => Some(Ordering::Greater),
+            _ => None,
+        };
+        if let Some(ordering) = ordering {
+            return if flipped {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+        }
+
+        // a / da < b / db exactly when da / a > db / b
+        let ordering = (db / b).cmp(&(da / a));
+        if ordering != Ordering::Equal {
+            return if flipped {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+        }
+        (a, da, b, db) = (da % a, a, db % b, b);
+        flipped = !flipped;
+    }
+}
+
+/// An exact fraction over `i128`. Arithmetic is checked and returns `None` when the result or
+/// an intermediate product does not fit, comparison never overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numer: i128,
+    denom: i128,
+}
+
+impl Ratio {
+    /// Reduces `numer / denom`, `None` for a zero denominator or when the reduced fraction
+    /// does not fit (`i128::MIN / -1`)
+    pub fn new(numer: i128, denom: i128) -> Option<Self> {
+        if denom == 0 {
+            return None;
+        }
+        let negative = (numer < 0) != (denom < 0);
+        let g = gcd(numer.unsigned_abs(), denom.unsigned_abs());
+        let numer = numer.unsigned_abs() / g;
+        let numer = if negative {
+            0i128.checked_sub_unsigned(numer)?
+        } else {
+            i128::try_from(numer).ok()?
+        };
+        let denom = i128::try_from(denom.unsigned_abs() / g).ok()?;
+        Some(Ratio { numer, denom })
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        if self.denom == 1 {
+            Some(self.numer)
+        } else {
+            None
+        }
+    }
+
+    pub fn floor(self) -> i128 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Ratio {
+            numer: self.numer.checked_neg()?,
+            denom: self.denom,
+        })
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let g = gcd(self.denom as u128, rhs.denom as u128) as i128;
+        let l = self.denom / g;
+        let r = rhs.denom / g;
+        Ratio::new(
+            self.numer
+                .checked_mul(r)?
+                .checked_add(rhs.numer.checked_mul(l)?)?,
+            self.denom.checked_mul(r)?,
+        )
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let a = gcd(self.numer.unsigned_abs(), rhs.denom as u128) as i128;
+        let b = gcd(rhs.numer.unsigned_abs(), self.denom as u128) as i128;
+        Ratio::new(
+            (self.numer / a).checked_mul(rhs.numer / b)?,
+            (self.denom / b).checked_mul(rhs.denom / a)?,
+        )
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_mul(Ratio::new(rhs.denom, rhs.numer)?)
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(value: i128) -> Self {
+        Ratio {
+            numer: value,
+            denom: 1,
+        }
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare whole parts first so only proper fractions are left
+        self.floor().cmp(&other.floor()).then_with(|| {
+            cmp_proper(
+                self.numer.rem_euclid(self.denom),
+                self.denom,
+                other.numer.rem_euclid(other.denom),
+                other.denom,
+            )
+        })
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes() {
+        assert_eq!(Ratio::new(4, -6), Ratio::new(-2, 3));
+        assert_eq!(Ratio::new(0, -5), Some(Ratio::from(0)));
+        assert_eq!(Ratio::new(-7, 2).unwrap().floor(), -4);
+        assert_eq!(Ratio::new(i128::MIN, 2), Some(Ratio::from(i128::MIN / 2)));
+
+        let sum = Ratio::new(1, 3)
+            .unwrap()
+            .checked_add(Ratio::new(1, 6).unwrap());
+        assert_eq!(sum.unwrap().to_string(), "1/2");
+    }
+
+    #[test]
+    fn reports_overflow() {
+        assert_eq!(Ratio::new(1, 0), None);
+        assert_eq!(Ratio::new(i128::MIN, -1), None);
+        assert_eq!(Ratio::from(i128::MIN).checked_neg(), None);
+        assert_eq!(Ratio::from(i128::MAX).checked_add(Ratio::from(1)), None);
+        assert_eq!(Ratio::from(i128::MIN).checked_sub(Ratio::from(1)), None);
+        assert_eq!(Ratio::from(1 << 64).checked_mul(Ratio::from(1 << 64)), None);
+        assert_eq!(Ratio::from(1).checked_div(Ratio::from(0)), None);
+        assert_eq!(
+            Ratio::from(1 << 64).checked_div(Ratio::new(1, 1 << 64).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn orders_without_overflow() {
+        let just_below = Ratio::new(2 * 400000000000000 - 1, 2).unwrap();
+        assert!(just_below < Ratio::from(400000000000000));
+        assert!(Ratio::new(-1, 2).unwrap() < Ratio::from(0));
+        assert!(Ratio::new(-1, 3) > Ratio::new(-1, 2));
+        assert_eq!(
+            Ratio::new(i128::MAX, 3).cmp(&Ratio::new(i128::MAX, 3)),
+            Ordering::Equal
+        );
+
+        let m = i128::MAX;
+        assert!(Ratio::new(m - 1, m) > Ratio::new(m - 2, m - 1));
+        assert!(Ratio::new(1 - m, m) < Ratio::new(2 - m, m - 1));
+        assert!(Ratio::new(m - 1, m) < Ratio::new(m, m - 1));
+        assert!(Ratio::from(i128::MIN) < Ratio::from(i128::MIN + 1));
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    env,
+    fmt::Write,
+    io::{self, Write as _},
+    thread,
+    time::Duration,
+};
+
+const FRAME_DELAY: Duration = Duration::from_millis(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+}
+
+impl Color {
+    fn code(&self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::Gray => 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<(char, Option<Color>)>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            cells: vec![(' ', None); width * height],
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: char, color: Option<Color>) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = (c, color);
+        }
+    }
+
+    pub fn render(&self, colors: bool) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = None;
+            for &(c, color) in row {
+                if colors && color != current {
+                    match color {
+                        Some(color) => write!(out, "\x1b[{}m", color.code()).unwrap(),
+                        None => out.push_str("\x1b[0m"),
+                    }
+                    current = color;
+                }
+                out.push(c);
+            }
+            if colors && current.is_some() {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub struct Animation {
+    colors: bool,
+    every: usize,
+    frames: usize,
+}
+
+impl Animation {
+    // Draws one frame out of every `every`, honouring NO_COLOR
+    pub fn new(every: usize) -> Self {
+        Animation {
+            colors: env::var_os("NO_COLOR").is_none(),
+            every: every.max(1),
+            frames: 0,
+        }
+    }
+
+    pub fn frame(&mut self, canvas: impl FnOnce() -> Canvas) {
+        self.frames += 1;
+        if (self.frames - 1).is_multiple_of(self.every) {
+            print!("\x1b[H\x1b[2J{}", canvas().render(self.colors));
+            io::stdout().flush().unwrap();
+            thread::sleep(FRAME_DELAY);
+        }
+    }
+
+    pub fn show(&self, canvas: &Canvas) {
+        println!("{}", canvas.render(self.colors));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_with_and_without_colors() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas.set(0, 0, '#', None);
+        canvas.set(1, 0, 'O', Some(Color::Yellow));
+        canvas.set(2, 0, 'O', Some(Color::Yellow));
+        canvas.set(5, 5, 'X', None);
+
+        assert_eq!(canvas.render(false), "#OO\n   \n");
+        assert_eq!(canvas.render(true), "#\x1b[33mOO\x1b[0m\n   \n");
+    }
+}
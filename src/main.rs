@@ -1,22 +1,66 @@
-use std::{
-    collections::HashMap,
-    fmt::Debug,
-    fs,
-    io::{BufRead, BufReader},
-    process::exit,
-};
+use std::{fmt::Debug, path::PathBuf, process::exit};
 
 use structopt::StructOpt;
 
+mod answers;
+mod cache;
 mod days;
+mod scaffold;
+mod watch;
 
-use day::Day;
+use answers::{AnswerStore, Check, Verdict};
+use cache::ResultCache;
+use day::RunOptions;
 use days::*;
+use input::Input;
+use profile::Format;
 
 #[derive(StructOpt)]
 struct Opt {
     #[structopt(name = "day")]
-    day: i32,
+    day: Option<i32>,
+    #[structopt(long)]
+    reference: bool,
+    #[structopt(long)]
+    no_cache: bool,
+    #[structopt(long)]
+    visualize: bool,
+    #[structopt(long, parse(from_os_str))]
+    export: Option<PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    graph: Option<PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    profile: Option<PathBuf>,
+    #[structopt(long)]
+    verbose: bool,
+    #[structopt(long = "param", parse(try_from_str = parse_param))]
+    params: Vec<(String, String)>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    Mark {
+        day: i32,
+        part: u8,
+        verdict: Verdict,
+        #[structopt(long)]
+        answer: Option<String>,
+    },
+    Watch {
+        day: i32,
+    },
+    New {
+        day: i32,
+    },
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or(format!("Expected key=value, got {}", s))?;
+    Ok((key.to_owned(), value.to_owned()))
 }
 
 fn default_error_handler<E: Debug, R>(error: E) -> R {
@@ -26,45 +70,121 @@ fn default_error_handler<E: Debug, R>(error: E) -> R {
 
 fn main() {
     let opt = Opt::from_args();
-    let mut programs: HashMap<i32, Box<dyn Day>> = HashMap::new();
-    programs.insert(1, Box::new(day1::Instance));
-    programs.insert(2, Box::new(day2::Instance));
-    programs.insert(3, Box::new(day3::Instance));
-    programs.insert(4, Box::new(day4::Instance));
-    programs.insert(5, Box::new(day5::Instance));
-    programs.insert(6, Box::new(day6::Instance));
-    programs.insert(7, Box::new(day7::Instance));
-    programs.insert(8, Box::new(day8::Instance));
-    programs.insert(9, Box::new(day9::Instance));
-    programs.insert(10, Box::new(day10::Instance));
-    programs.insert(11, Box::new(day11::Instance));
-    programs.insert(12, Box::new(day12::Instance));
-    programs.insert(13, Box::new(day13::Instance));
-    programs.insert(14, Box::new(day14::Instance));
-    programs.insert(15, Box::new(day15::Instance));
-    programs.insert(16, Box::new(day16::Instance));
-    programs.insert(17, Box::new(day17::Instance));
-    programs.insert(18, Box::new(day18::Instance));
-    programs.insert(19, Box::new(day19::Instance));
-    programs.insert(20, Box::new(day20::Instance));
-    programs.insert(21, Box::new(day21::Instance));
-    programs.insert(22, Box::new(day22::Instance));
-    programs.insert(23, Box::new(day23::Instance));
-    programs.insert(24, Box::new(day24::Instance));
-    programs.insert(25, Box::new(day25::Instance));
+    if let Some(Command::New { day }) = opt.command {
+        let created = scaffold::new_day(day).unwrap_or_else(default_error_handler);
+        for path in created {
+            println!("Created {}", path.display());
+        }
+        return;
+    }
+
+    let programs = days::registry();
+
+    if let Some(Command::Mark {
+        day,
+        part,
+        verdict,
+        answer,
+    }) = opt.command
+    {
+        let input =
+            Input::read(format!("input/day{}.txt", day)).unwrap_or_else(default_error_handler);
+        let mut store = AnswerStore::load(day).unwrap_or_else(default_error_handler);
+        let answer = store
+            .mark(part, answer.as_deref(), input.fingerprint(), verdict)
+            .unwrap_or_else(default_error_handler);
+        println!("Day {} part {}: {} marked {}", day, part, answer, verdict);
+        return;
+    }
 
+    let day = match opt.command {
+        Some(Command::Watch { day }) => day,
+        _ => opt
+            .day
+            .unwrap_or_else(|| default_error_handler("Missing day")),
+    };
     let program = programs
-        .get(&opt.day)
-        .unwrap_or_else(|| default_error_handler(format!("Undefined day: {}", opt.day).as_str()));
-    let file_contents: Vec<String> = fs::File::open(format!("input/day{}.txt", opt.day))
-        .and_then(|file| BufReader::new(file).lines().collect())
-        .unwrap_or_else(default_error_handler);
-    let result = program
-        .run(file_contents)
+        .get(&day)
+        .unwrap_or_else(|| default_error_handler(format!("Undefined day: {}", day).as_str()));
+    let options = RunOptions {
+        reference: opt.reference,
+        visualize: opt.visualize,
+        export: opt.export.clone(),
+        graph: opt.graph.clone(),
+        verbose: opt.verbose,
+        params: opt.params.clone(),
+    };
+
+    if let Some(Command::Watch { .. }) = opt.command {
+        watch::watch(day, program.as_ref(), &options);
+        return;
+    }
+
+    let input = Input::read(format!("input/day{}.txt", day)).unwrap_or_else(default_error_handler);
+    let hash = program
+        .extra_inputs(&options)
+        .iter()
+        .try_fold(input.fingerprint(), |hash, path| {
+            Input::read(path)
+                .map(|extra| extra.fingerprint_from(hash))
+                .map_err(|e| format!("{}: {}", path.display(), e))
+        })
         .unwrap_or_else(default_error_handler);
+    let mut cache = ResultCache::load(day);
+    let key = ResultCache::key(hash, program.version(), &options);
+    if let Some(path) = &opt.profile {
+        profile::enable(Format::from_path(path)).unwrap_or_else(default_error_handler);
+    }
+    // Runs with side effects always recompute
+    let side_effects = opt.profile.is_some()
+        || opt.visualize
+        || opt.verbose
+        || opt.export.is_some()
+        || opt.graph.is_some();
+    let use_cache = !opt.no_cache && !side_effects;
+    let cached = cache.get(&key).filter(|_| use_cache).cloned();
+    let is_cached = cached.is_some();
+    let result = cached.unwrap_or_else(|| {
+        let result = program
+            .run_input(&input, &options)
+            .unwrap_or_else(default_error_handler);
+        cache
+            .insert(key, &result)
+            .unwrap_or_else(default_error_handler);
+        result
+    });
+    if let Some(path) = &opt.profile {
+        profile::write(path).unwrap_or_else(default_error_handler);
+        println!("Profile written to {}", path.display());
+    }
+
+    let mut store = AnswerStore::load(day).unwrap_or_else(default_error_handler);
+    // Parameters change the question, so those answers are not the puzzle's
+    let puzzle_answers = options.params.is_empty();
+    let parts = [Some(result.part1), result.part2];
+    for (part, answer) in (1..).zip(parts) {
+        let Some(answer) = answer else { continue };
+        let check = if puzzle_answers {
+            store.check(part, &answer, hash)
+        } else {
+            Check::New
+        };
+        if is_cached {
+            println!("Part {}: {} (cached){}", part, answer, check);
+        } else {
+            if puzzle_answers {
+                store
+                    .record(part, &answer, hash)
+                    .unwrap_or_else(default_error_handler);
+            }
+            println!("Part {}: {}{}", part, answer, check);
+        }
+    }
 
-    println!("Part 1: {}", result.part1);
-    if let Some(v) = result.part2 {
-        println!("Part 2: {}", v)
+    let queries = program
+        .queries(&input, &options)
+        .unwrap_or_else(default_error_handler);
+    for (question, answer) in queries {
+        println!("{}: {}", question, answer);
     }
 }
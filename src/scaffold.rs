@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const TEMPLATE: &str = include_str!("../templates/day.rs.tmpl");
+const DAYS_MOD: &str = "src/days/mod.rs";
+
+fn create(path: PathBuf, contents: &str) -> Result<PathBuf, String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn declare(day: i32) -> Result<(), String> {
+    let path = Path::new(DAYS_MOD);
+    let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut lines: Vec<&str> = source.lines().collect();
+    // After the last earlier day, so the declarations stay in order
+    let position = lines
+        .iter()
+        .rposition(|line| {
+            line.strip_prefix("pub mod day")
+                .and_then(|rest| rest.strip_suffix(';'))
+                .and_then(|n| n.parse::<i32>().ok())
+                .is_some_and(|n| n < day)
+        })
+        .map_or(lines.len(), |i| i + 1);
+    let declaration = format!("pub mod day{};", day);
+    lines.insert(position, &declaration);
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    fs::write(path, updated).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn new_day(day: i32) -> Result<Vec<PathBuf>, String> {
+    if day < 1 {
+        return Err(format!("Invalid day: {}", day));
+    }
+
+    let module = PathBuf::from(format!("src/days/day{}.rs", day));
+    if module.exists() {
+        return Err(format!("{} already exists", module.display()));
+    }
+
+    let mut created = vec![create(
+        module,
+        &TEMPLATE.replace("{day}", &day.to_string()),
+    )?];
+    declare(day)?;
+    for path in [
+        format!("fixtures/day{}.txt", day),
+        format!("answers/day{}.txt", day),
+    ] {
+        if !Path::new(&path).exists() {
+            created.push(create(PathBuf::from(path), "")?);
+        }
+    }
+    Ok(created)
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::days::{
+    day::{Day, DayResult, RunOptions},
+    input::Input,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub fn fixtures(day: i32) -> Vec<PathBuf> {
+    let name = format!("day{}", day);
+    let mut paths: Vec<_> = fs::read_dir("fixtures")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_stem()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s == name || s.starts_with(&format!("{}-", name)))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn run_file(program: &dyn Day, path: &Path, options: &RunOptions) -> Result<DayResult, String> {
+    let input = Input::read(path).map_err(|e| e.to_string())?;
+    panic::catch_unwind(AssertUnwindSafe(|| program.run_input(&input, options)))
+        .map_err(|_| "Solver panicked".to_owned())?
+}
+
+// Paths that are new or modified since the last poll
+fn changed(paths: Vec<PathBuf>, seen: &mut HashMap<PathBuf, SystemTime>) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .filter(|path| {
+            let Some(stamp) = modified(path) else {
+                return false;
+            };
+            seen.insert(path.clone(), stamp) != Some(stamp)
+        })
+        .collect()
+}
+
+fn describe(part: usize, answer: &str, previous: Option<&str>) -> String {
+    match previous {
+        Some(p) if p == answer => format!("Part {}: {} (unchanged)", part, answer),
+        Some(p) => format!("Part {}: {} (was {})", part, answer, p),
+        None => format!("Part {}: {}", part, answer),
+    }
+}
+
+pub fn watch(day: i32, program: &dyn Day, options: &RunOptions) {
+    let mut seen: HashMap<PathBuf, SystemTime> = HashMap::new();
+    let mut previous: HashMap<PathBuf, DayResult> = HashMap::new();
+
+    println!("Watching day {}, press Ctrl-C to stop", day);
+    loop {
+        let mut paths = vec![PathBuf::from(format!("input/day{}.txt", day))];
+        paths.extend(fixtures(day));
+
+        for path in changed(paths, &mut seen) {
+            let start = Instant::now();
+            let result = run_file(program, &path, options);
+            println!("== {} ({:.2?})", path.display(), start.elapsed());
+
+            match result {
+                Ok(result) => {
+                    let before = previous.get(&path);
+                    println!(
+                        "{}",
+                        describe(1, &result.part1, before.map(|r| r.part1.as_str()))
+                    );
+                    if let Some(part2) = &result.part2 {
+                        println!(
+                            "{}",
+                            describe(2, part2, before.and_then(|r| r.part2.as_deref()))
+                        );
+                    }
+                    previous.insert(path, result);
+                }
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_fixtures_by_day() {
+        let fixtures = fixtures(5);
+        assert!(fixtures.contains(&PathBuf::from("fixtures/day5.txt")));
+        assert!(fixtures.iter().all(|p| !p.ends_with("day25.txt")));
+    }
+
+    #[test]
+    fn detects_changes() {
+        let dir = std::env::temp_dir().join(format!("aoc23-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("day1.txt");
+        let missing = dir.join("day1-missing.txt");
+        fs::write(&path, "1").unwrap();
+
+        let mut seen = HashMap::new();
+        let paths = || vec![path.clone(), missing.clone()];
+        assert_eq!(changed(paths(), &mut seen), vec![path.clone()]);
+        assert!(changed(paths(), &mut seen).is_empty());
+
+        let later = modified(&path).unwrap() + Duration::from_secs(1);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(later))
+            .unwrap();
+        assert_eq!(changed(paths(), &mut seen), vec![path.clone()]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn describes_differences() {
+        assert_eq!(describe(1, "42", None), "Part 1: 42");
+        assert_eq!(describe(1, "42", Some("42")), "Part 1: 42 (unchanged)");
+        assert_eq!(describe(2, "42", Some("41")), "Part 2: 42 (was 41)");
+    }
+}